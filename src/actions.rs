@@ -1,9 +1,21 @@
+pub mod copy_snippet_to_file;
 pub mod create_directory_and_file;
 pub mod edit_snippet_in_file;
+pub mod edit_snippet_interactively;
+pub mod expand_snippet;
+pub mod format_snippets;
+pub mod full_text_search;
+pub mod grep_snippets;
+pub mod insert_snippet;
+pub mod lint_snippets;
+pub mod list_collections;
 pub mod list_snippets;
 pub mod open_file_with;
 pub mod remove_snippet_from_file;
+pub mod replay_change;
+pub mod run_tui;
 pub mod search_snippets;
+pub mod show_history;
 pub mod show_snippet;
 pub mod update_key_in_file;
 pub mod write_snippet_to_file;
@@ -0,0 +1,66 @@
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::models::snippet_change_model::{Operation, SnippetChange, SnippetEntry};
+use crate::models::snippet_document_model::SnippetDocument;
+use crate::models::snippet_model::Snippet;
+use anyhow::Context;
+use std::fs;
+use std::path::Path;
+
+/// Adds an existing snippet, unknown fields included, to another collection file, e.g. to
+/// override a user snippet in the project layer.
+pub fn copy_snippet_to_file(
+    file_path: &str,
+    key: &str,
+    snippet: &Snippet,
+) -> anyhow::Result<SnippetChange> {
+    let path = Path::new(file_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create directory")?;
+    }
+
+    // Hold the lock until the updated file is in place so concurrent runs don't drop changes
+    let _lock = FileLock::acquire(path)?;
+
+    let mut snippets = if path.exists() && path.metadata()?.len() > 0 {
+        let file_contents =
+            fs::read_to_string(file_path).context("Failed to read the snippets file")?;
+        SnippetDocument::parse(file_contents).context("Failed to parse the snippets file")?
+    } else {
+        SnippetDocument::default()
+    };
+
+    let original_text = snippets.text().to_string();
+    snippets.insert(key, snippet)?;
+    atomic_write(path, snippets.text()).context("Failed to write to the snippets file")?;
+
+    Ok(SnippetChange::new(
+        file_path,
+        Operation::Add,
+        None,
+        Some(SnippetEntry::new(key, snippet)),
+        &original_text,
+        snippets.text(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_copy_snippet_keeps_unknown_fields() {
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join(".snip").join("rust.json");
+        let snippet: Snippet =
+            serde_json::from_str(r#"{ "prefix": "a", "body": "b", "isFileTemplate": true }"#)
+                .unwrap();
+
+        copy_snippet_to_file(target.to_str().unwrap(), "a", &snippet).unwrap();
+
+        let copied = SnippetDocument::parse(fs::read_to_string(&target).unwrap()).unwrap();
+        assert_eq!(copied.get("a"), Some(&snippet));
+        assert!(copy_snippet_to_file(target.to_str().unwrap(), "a", &snippet).is_err());
+    }
+}
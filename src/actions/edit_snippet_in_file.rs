@@ -1,37 +1,48 @@
-use crate::models::snippet_model::Snippet;
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::models::snippet_change_model::{Operation, SnippetChange, SnippetEntry};
+use crate::models::snippet_document_model::SnippetDocument;
 use anyhow::Context;
-use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 pub fn edit_snippet_in_file(
     file_path: &str,
     key: String,
-    new_prefix: Option<String>,
+    new_prefix: Option<Vec<String>>,
     new_description: Option<String>,
+    new_scope: Option<String>,
     new_body: Option<Vec<String>>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<SnippetChange> {
     // If all fields are None, do not proceed
-    if new_prefix.is_none() && new_description.is_none() && new_body.is_none() {
+    if new_prefix.is_none()
+        && new_description.is_none()
+        && new_scope.is_none()
+        && new_body.is_none()
+    {
         return Err(anyhow::anyhow!("No fields provided for update"));
     }
 
     let path = Path::new(file_path);
 
-    let mut snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
+    // Hold the lock until the updated file is in place so concurrent runs don't drop changes
+    let _lock = FileLock::acquire(path)?;
+
+    let mut snippets = if path.exists() && path.metadata()?.len() > 0 {
         let file_contents =
             fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
+        SnippetDocument::parse(file_contents).context("Failed to parse the snippets file")?
     } else {
         return Err(anyhow::anyhow!("Snippet file not found or is empty"));
     };
 
     // Check for duplicate prefix in other snippets
-    if let Some(ref prefix) = new_prefix {
-        if snippets
-            .iter()
-            .any(|(k, s)| k != &key && s.prefix == *prefix)
-        {
+    if let Some(ref prefixes) = new_prefix {
+        if let Some(prefix) = prefixes.iter().find(|p| {
+            snippets
+                .iter()
+                .any(|(k, s)| k != key && s.prefix.contains(p))
+        }) {
             return Err(anyhow::anyhow!(
                 "A snippet with prefix '{}' already exists",
                 prefix
@@ -39,33 +50,48 @@ pub fn edit_snippet_in_file(
         }
     }
 
-    // Get mutable reference to the snippet after checking for duplicates
-    let snippet = snippets
-        .get_mut(&key)
+    // Get a copy of the snippet after checking for duplicates
+    let mut snippet = snippets
+        .get(&key)
+        .cloned()
         .ok_or_else(|| anyhow::anyhow!("Snippet with key '{}' not found", key))?;
 
-    // Update the snippet's fields if new values are provided
+    // Update the snippet's fields if new values are provided, keeping their JSON shape
     if let Some(prefix) = new_prefix {
-        snippet.prefix = prefix;
+        snippet.prefix.replace(prefix);
     }
     if let Some(description) = new_description {
-        snippet.description = description;
+        snippet.description = Some(description);
+    }
+    // An empty scope offers the snippet for every language again
+    if let Some(scope) = new_scope {
+        snippet.scope = Some(scope).filter(|scope| !scope.trim().is_empty());
     }
     if let Some(body) = new_body {
-        snippet.body = body;
+        snippet.body.replace(body);
     }
 
-    // Write the updated HashMap back to the JSON file
-    let updated_contents =
-        serde_json::to_string_pretty(&snippets).context("Failed to serialize snippets")?;
-    fs::write(file_path, updated_contents).context("Failed to write to the snippets file")?;
-
-    Ok(())
+    // Only the changed fields are rewritten, the rest of the file stays as it was
+    let original_text = snippets.text().to_string();
+    let before = SnippetEntry::new(&key, snippets.get(&key).expect("the snippet exists"));
+    snippets.replace(&key, &snippet)?;
+    atomic_write(path, snippets.text()).context("Failed to write to the snippets file")?;
+
+    Ok(SnippetChange::new(
+        file_path,
+        Operation::Edit,
+        Some(before),
+        Some(SnippetEntry::new(&key, &snippet)),
+        &original_text,
+        snippets.text(),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::snippet_model::Snippet;
+    use std::collections::HashMap;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -76,9 +102,10 @@ mod tests {
 
         let test_key = "test_key".to_string();
         let initial_snippet = Snippet {
-            prefix: "initial_prefix".to_string(),
-            description: "initial_description".to_string(),
-            body: vec!["initial_body".to_string()],
+            prefix: "initial_prefix".into(),
+            description: Some("initial_description".to_string()),
+            body: vec!["initial_body".to_string()].into(),
+            ..Default::default()
         };
 
         // Setup: Write an initial snippet
@@ -88,7 +115,14 @@ mod tests {
         write!(&temp_file, "{}", initial_contents).unwrap();
 
         // Attempt to edit with empty fields
-        let result = edit_snippet_in_file(test_path.to_str().unwrap(), test_key, None, None, None);
+        let result = edit_snippet_in_file(
+            test_path.to_str().unwrap(),
+            test_key,
+            None,
+            None,
+            None,
+            None,
+        );
         assert!(result.is_err());
 
         // No need for explicit cleanup, as NamedTempFile automatically deletes the file
@@ -107,17 +141,19 @@ mod tests {
         initial_snippets.insert(
             test_key_1.clone(),
             Snippet {
-                prefix: duplicate_prefix.clone(),
-                description: "description_1".to_string(),
-                body: vec!["body_1".to_string()],
+                prefix: duplicate_prefix.clone().into(),
+                description: Some("description_1".to_string()),
+                body: vec!["body_1".to_string()].into(),
+                ..Default::default()
             },
         );
         initial_snippets.insert(
             test_key_2.clone(),
             Snippet {
-                prefix: "prefix_2".to_string(),
-                description: "description_2".to_string(),
-                body: vec!["body_2".to_string()],
+                prefix: "prefix_2".into(),
+                description: Some("description_2".to_string()),
+                body: vec!["body_2".to_string()].into(),
+                ..Default::default()
             },
         );
 
@@ -128,12 +164,41 @@ mod tests {
         let result = edit_snippet_in_file(
             test_path.to_str().unwrap(),
             test_key_2.clone(),
-            Some(duplicate_prefix),
+            Some(vec![duplicate_prefix]),
             Some("new_description".to_string()),
+            None,
             Some(vec!["new_body".to_string()]),
         );
         assert!(result.is_err());
 
         // No need for explicit cleanup, as NamedTempFile automatically deletes the file
     }
+
+    #[test]
+    fn test_edit_keeps_shape_of_fields() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let test_path = temp_file.path().to_owned();
+
+        write!(
+            temp_file,
+            r#"{{ "test_key": {{ "prefix": ["a", "b"], "body": "old body" }} }}"#
+        )
+        .unwrap();
+
+        edit_snippet_in_file(
+            test_path.to_str().unwrap(),
+            "test_key".to_string(),
+            Some(vec!["c".to_string()]),
+            None,
+            None,
+            Some(vec!["new body".to_string()]),
+        )
+        .unwrap();
+
+        let contents = fs::read_to_string(&test_path).unwrap();
+        let snippets: HashMap<String, serde_json::Value> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(snippets["test_key"]["prefix"], serde_json::json!(["c"]));
+        assert_eq!(snippets["test_key"]["body"], serde_json::json!("new body"));
+        assert!(snippets["test_key"].get("description").is_none());
+    }
 }
@@ -0,0 +1,124 @@
+use crate::actions::copy_snippet_to_file::copy_snippet_to_file;
+use crate::actions::edit_snippet_in_file::edit_snippet_in_file;
+use crate::actions::update_key_in_file::update_key_in_file;
+use crate::models::snippet_change_model::SnippetChange;
+use crate::models::snippet_model::Snippet;
+use crate::models::snippet_template_model::SnippetTemplate;
+use anyhow::Context;
+
+/// Edits the snippet `key` of `file_path` as a template: `edit` gets the snippet's template,
+/// e.g. to open it in an editor, and only the fields it changed are written. A changed key
+/// renames the snippet.
+///
+/// With `copy`, the snippet is first copied into `file_path` to override it in that layer
+/// instead of changing the file it comes from. Returns the changes to journal, in order, none
+/// when the template came back unchanged.
+pub fn edit_snippet_interactively(
+    file_path: &str,
+    key: &str,
+    snippet: &Snippet,
+    copy: bool,
+    edit: impl FnOnce(&SnippetTemplate) -> anyhow::Result<SnippetTemplate>,
+) -> anyhow::Result<Vec<SnippetChange>> {
+    let template = SnippetTemplate::new(key, snippet);
+    let edited = edit(&template)?;
+    let mut changes = Vec::new();
+    if edited == template {
+        return Ok(changes);
+    }
+
+    let prefix = Some(edited.prefixes).filter(|p| *p != template.prefixes);
+    let description = Some(edited.description).filter(|d| *d != template.description);
+    let scope = Some(edited.scope).filter(|s| *s != template.scope);
+    let body = Some(edited.body).filter(|b| *b != template.body);
+    let has_changes =
+        prefix.is_some() || description.is_some() || scope.is_some() || body.is_some();
+    if copy {
+        let change = copy_snippet_to_file(file_path, key, snippet)
+            .context("Failed to copy snippet to the project layer")?;
+        changes.push(change);
+    }
+    if has_changes {
+        let change =
+            edit_snippet_in_file(file_path, key.to_string(), prefix, description, scope, body)
+                .context("Failed to edit snippet in file")?;
+        changes.push(change);
+    }
+    if edited.key != key {
+        let change = update_key_in_file(file_path, key.to_string(), edited.key)
+            .context("Failed to update key in file")?;
+        changes.push(change);
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::snippet_document_model::SnippetDocument;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn snippet() -> Snippet {
+        serde_json::from_str(r#"{ "prefix": "a", "description": "A", "body": "b" }"#).unwrap()
+    }
+
+    #[test]
+    fn test_edit_snippet_interactively() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("rust.json");
+        let file_path = path.to_str().unwrap();
+        fs::write(
+            &path,
+            r#"{ "a": { "prefix": "a", "description": "A", "body": "b" } }"#,
+        )?;
+
+        let unchanged =
+            edit_snippet_interactively(file_path, "a", &snippet(), false, |t| Ok(t.clone()))?;
+        assert!(unchanged.is_empty());
+
+        let changes = edit_snippet_interactively(file_path, "a", &snippet(), false, |t| {
+            Ok(SnippetTemplate {
+                key: "c".to_string(),
+                body: vec!["d".to_string()],
+                ..t.clone()
+            })
+        })?;
+        let keys: Vec<&str> = changes.iter().map(|change| change.key()).collect();
+        assert_eq!(keys, ["a", "c"]);
+        let document = SnippetDocument::parse(fs::read_to_string(&path)?)?;
+        assert_eq!(document.get("a"), None);
+        assert_eq!(document.get("c").unwrap().body.to_vec(), ["d"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_snippet_interactively_copies_into_layer() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join(".snip").join("rust.json");
+        let file_path = path.to_str().unwrap();
+
+        let changes = edit_snippet_interactively(file_path, "a", &snippet(), true, |t| {
+            Ok(SnippetTemplate {
+                description: "B".to_string(),
+                ..t.clone()
+            })
+        })?;
+
+        assert_eq!(changes.len(), 2);
+        let document = SnippetDocument::parse(fs::read_to_string(&path)?)?;
+        assert_eq!(document.get("a").unwrap().description(), "B");
+
+        // A new key alone also overrides the snippet, under that key
+        let changes = edit_snippet_interactively(file_path, "b", &snippet(), true, |t| {
+            Ok(SnippetTemplate {
+                key: "c".to_string(),
+                ..t.clone()
+            })
+        })?;
+        assert_eq!(changes.len(), 2);
+        let document = SnippetDocument::parse(fs::read_to_string(&path)?)?;
+        assert_eq!(document.get("c"), Some(&snippet()));
+        Ok(())
+    }
+}
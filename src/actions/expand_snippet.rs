@@ -0,0 +1,83 @@
+use crate::helpers::render_json::render_json;
+use crate::models::expand_context_model::ExpandContext;
+use crate::models::expansion_model::ExpansionRecord;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::snippet_source_model::SnippetSource;
+use crate::parser::render_snippet_ast::render_snippet_ast;
+use crate::parser::snippet_ast::SnippetAst;
+
+/// Renders the snippet with the key or prefix `name` to its final text. JSON output also
+/// reports where `$0` leaves the cursor.
+///
+/// `fill` can add values to the context once the snippet is found, e.g. by prompting for them.
+pub fn expand_snippet(
+    sources: &[SnippetSource],
+    name: &str,
+    mut context: ExpandContext,
+    fill: impl FnOnce(&SnippetAst, &mut ExpandContext) -> anyhow::Result<()>,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let snippets = LayeredSnippets::load(sources)?;
+    let (key, snippet, _) = snippets
+        .find(name)
+        .ok_or_else(|| anyhow::anyhow!("No snippet with key or prefix '{}' found", name))?;
+
+    let ast = snippet.body_ast();
+    fill(&ast, &mut context)?;
+    let expansion = render_snippet_ast(&ast, &context);
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            render_json(&ExpansionRecord::new(key, expansion), format)
+        }
+        OutputFormat::Table | OutputFormat::Plain => Ok(expansion.text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_expand_snippet_by_prefix() -> anyhow::Result<()> {
+        let test_file = NamedTempFile::new()?;
+        fs::write(
+            test_file.path(),
+            r##"{ "test": { "prefix": ["tst", "test"], "body": ["#[test]", "fn ${1:it_works}() {", "\t$0", "}"] } }"##,
+        )?;
+        let sources = [SnippetSource::user(test_file.path().to_str().unwrap())];
+
+        let output = expand_snippet(
+            &sources,
+            "tst",
+            ExpandContext::default(),
+            |_, _| Ok(()),
+            OutputFormat::Plain,
+        )?;
+        assert_eq!(output, "#[test]\nfn it_works() {\n\t\n}");
+
+        let output = expand_snippet(
+            &sources,
+            "test",
+            ExpandContext::default(),
+            |_, _| Ok(()),
+            OutputFormat::Jsonl,
+        )?;
+        assert_eq!(
+            output,
+            r##"{"key":"test","text":"#[test]\nfn it_works() {\n\t\n}","cursor":{"offset":25,"line":3,"column":2}}"##
+        );
+
+        assert!(expand_snippet(
+            &sources,
+            "nope",
+            ExpandContext::default(),
+            |_, _| Ok(()),
+            OutputFormat::Plain
+        )
+        .is_err());
+        Ok(())
+    }
+}
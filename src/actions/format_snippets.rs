@@ -0,0 +1,124 @@
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::helpers::format_snippet_document::format_snippet_document;
+use crate::models::format_report_model::FormatReport;
+use crate::models::key_order_model::KeyOrder;
+use crate::models::snippet_change_model::{Operation, SnippetChange};
+use crate::models::snippet_document_model::SnippetDocument;
+use anyhow::Context;
+use std::fs;
+use std::path::Path;
+
+/// Rewrites collection files in their canonical form, missing files are skipped.
+///
+/// Returns the files that weren't formatted. With `check` they are left untouched, otherwise
+/// each rewrite is returned as a change, so it can be journaled.
+pub fn format_snippets(
+    paths: &[String],
+    key_order: KeyOrder,
+    check: bool,
+) -> anyhow::Result<FormatReport> {
+    let mut report = FormatReport::default();
+    for file_path in paths {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            log::info!("Skipping {}, it doesn't exist", file_path);
+            continue;
+        }
+        let _lock = (!check).then(|| FileLock::acquire(path)).transpose()?;
+        let file_contents =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", file_path))?;
+        let document = SnippetDocument::parse(file_contents)
+            .with_context(|| format!("Failed to parse {}", file_path))?;
+        let formatted = format_snippet_document(&document, key_order)
+            .with_context(|| format!("Failed to format {}", file_path))?;
+        if formatted == document.text() {
+            continue;
+        }
+        if !check {
+            atomic_write(path, &formatted)
+                .with_context(|| format!("Failed to write {}", file_path))?;
+            report.changes.push(SnippetChange::whole_file(
+                file_path,
+                Operation::Format,
+                document.text(),
+                &formatted,
+            ));
+        }
+        report.unformatted.push(file_path.clone());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::replay_change::{replay_change, Replay};
+    use crate::actions::write_snippet_to_file::write_snippet_to_file;
+    use crate::models::journal_model::Journal;
+    use tempfile::{tempdir, NamedTempFile};
+
+    #[test]
+    fn test_format_snippets_with_check() -> anyhow::Result<()> {
+        let test_file = NamedTempFile::new()?;
+        let text = r#"{"b": {"prefix": "b", "body": "b"}, "a": {"prefix": "a", "body": "a"}}"#;
+        fs::write(test_file.path(), text)?;
+        let paths = [test_file.path().to_str().unwrap().to_string()];
+
+        let report = format_snippets(&paths, KeyOrder::Sorted, true)?;
+        assert_eq!(
+            (report.unformatted, report.changes),
+            (paths.to_vec(), vec![])
+        );
+        assert_eq!(fs::read_to_string(test_file.path())?, text);
+
+        assert_eq!(
+            format_snippets(&paths, KeyOrder::Sorted, false)?.unformatted,
+            paths
+        );
+        assert!(format_snippets(&paths, KeyOrder::Sorted, true)?
+            .unformatted
+            .is_empty());
+        assert!(fs::read_to_string(test_file.path())?.starts_with("{\n  \"a\": {"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_after_format() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let journal_path = dir.path().join("journal.json");
+        let path = dir.path().join("rust.json");
+        let file_path = path.to_str().unwrap();
+        fs::write(&path, r#"{"b": {"prefix": "b", "body": "b"}}"#)?;
+        let change = write_snippet_to_file(
+            file_path,
+            "a".to_string(),
+            vec!["a".to_string()],
+            "A".to_string(),
+            None,
+            vec!["a".to_string()],
+        )?;
+        Journal::record(&journal_path, change)?;
+        let added = fs::read_to_string(&path)?;
+
+        let report = format_snippets(&[file_path.to_string()], KeyOrder::Sorted, false)?;
+        for change in report.changes {
+            Journal::record(&journal_path, change)?;
+        }
+        let formatted = fs::read_to_string(&path)?;
+        assert_ne!(formatted, added);
+
+        // Both the format and the change recorded before it can be undone and redone
+        replay_change(&journal_path, Replay::Undo)?;
+        assert_eq!(fs::read_to_string(&path)?, added);
+        replay_change(&journal_path, Replay::Undo)?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            r#"{"b": {"prefix": "b", "body": "b"}}"#
+        );
+        replay_change(&journal_path, Replay::Redo)?;
+        replay_change(&journal_path, Replay::Redo)?;
+        assert_eq!(fs::read_to_string(&path)?, formatted);
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::helpers::highlight_matches::highlight_matches;
+use crate::helpers::render_json::render_json;
+use crate::helpers::render_snippet_table::render_snippet_table;
+use crate::helpers::tokenize_code::tokenize_code;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::search_hit_model::SearchHit;
+use crate::models::search_index_model::SearchIndex;
+use crate::models::snippet_model::Snippet;
+use crate::models::snippet_record_model::SnippetRecord;
+use crate::models::snippet_source_model::SnippetSource;
+use std::collections::HashSet;
+
+/// Ranks the visible snippets by how well their key, prefixes, description and body match
+/// the words of `query`, see `SearchIndex`. Plain output has one snippet per line, followed
+/// by the layer it comes from and the body line with the most words of the query.
+///
+/// With `highlight`, the words of the query are marked in that line with ANSI colors.
+pub fn full_text_search(
+    sources: &[SnippetSource],
+    query: &str,
+    lang: Option<&str>,
+    format: OutputFormat,
+    highlight: bool,
+) -> anyhow::Result<String> {
+    let terms: HashSet<String> = tokenize_code(query).into_iter().collect();
+    if terms.is_empty() {
+        return Err(anyhow::anyhow!("The search has no words to look for"));
+    }
+    let snippets = LayeredSnippets::load(sources)?;
+    let entries: Vec<_> = snippets
+        .iter()
+        .filter(|(_, snippet, _)| lang.is_none_or(|lang| snippet.applies_to(lang)))
+        .collect();
+    let mut index = SearchIndex::default();
+    for (key, snippet, _) in &entries {
+        index.add(key, snippet);
+    }
+
+    let hits: Vec<SearchHit> = index
+        .search(query)
+        .into_iter()
+        .map(|(id, score)| {
+            let (key, snippet, source) = entries[id];
+            let context = best_line(snippet, &terms);
+            SearchHit {
+                record: SnippetRecord::new(key, snippet, source),
+                score,
+                line: context.as_ref().map(|(line, _)| *line),
+                context: context.map(|(_, text)| text),
+            }
+        })
+        .collect();
+
+    let output = match format {
+        OutputFormat::Json | OutputFormat::Jsonl => render_json(&hits, format)?,
+        OutputFormat::Table => {
+            let records: Vec<SnippetRecord> = hits.into_iter().map(|hit| hit.record).collect();
+            render_snippet_table(&records)
+        }
+        OutputFormat::Plain => hits
+            .iter()
+            .map(|hit| {
+                let mut line = format!("{}\t{}", hit.record.key, hit.record.layer);
+                if let (Some(number), Some(context)) = (hit.line, &hit.context) {
+                    let context = match highlight {
+                        true => highlight_terms(context, &terms),
+                        false => context.clone(),
+                    };
+                    line.push_str(&format!("\t{}: {}", number, context));
+                }
+                line + "\n"
+            })
+            .collect(),
+    };
+    Ok(output)
+}
+
+/// The first body line with the most of `terms`, numbered from 1 and trimmed.
+fn best_line(snippet: &Snippet, terms: &HashSet<String>) -> Option<(usize, String)> {
+    let body = snippet.body_text();
+    let mut best: Option<(usize, usize, &str)> = None;
+    for (index, line) in body.lines().enumerate() {
+        let found: HashSet<String> = tokenize_code(line)
+            .into_iter()
+            .filter(|term| terms.contains(term))
+            .collect();
+        if !found.is_empty() && best.is_none_or(|(count, _, _)| found.len() > count) {
+            best = Some((found.len(), index + 1, line.trim()));
+        }
+    }
+    best.map(|(_, number, line)| (number, line.to_string()))
+}
+
+/// Highlights the words of `line` holding one of `terms`.
+fn highlight_terms(line: &str, terms: &HashSet<String>) -> String {
+    let mut positions = Vec::new();
+    let mut word = String::new();
+    let mut start = 0;
+    for (index, c) in line.chars().chain(std::iter::once(' ')).enumerate() {
+        if c.is_alphanumeric() || c == '_' {
+            if word.is_empty() {
+                start = index;
+            }
+            word.push(c);
+        } else if !word.is_empty() {
+            if tokenize_code(&word).iter().any(|term| terms.contains(term)) {
+                positions.extend(start..index);
+            }
+            word.clear();
+        }
+    }
+    highlight_matches(line, &positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_full_text_search() -> anyhow::Result<()> {
+        let test_file = NamedTempFile::new()?;
+        fs::write(
+            test_file.path(),
+            r#"{
+  "deref": { "prefix": "deref", "body": ["impl std::ops::Deref for $1 {", "    type Target = $2;", "}"] },
+  "deref_mut": { "prefix": "derefm", "body": ["impl std::ops::DerefMut for $1 {", "    fn deref_mut(&mut self) -> &mut $2 {", "    }", "}"] },
+  "clone": { "prefix": "cl", "body": "impl Clone for $1 {}" }
+}"#,
+        )?;
+        let sources = [SnippetSource::user(test_file.path().to_str().unwrap())];
+
+        let output = full_text_search(&sources, "DerefMut", None, OutputFormat::Plain, false)?;
+        assert_eq!(
+            output,
+            "deref_mut\tuser\t1: impl std::ops::DerefMut for $1 {\nderef\tuser\t1: impl std::ops::Deref for $1 {\n"
+        );
+
+        let output = full_text_search(&sources, "deref_mut", None, OutputFormat::Plain, true)?;
+        assert!(output.starts_with(
+            "deref_mut\tuser\t2: fn \x1b[1;33mderef_mut\x1b[0m(&\x1b[1;33mmut\x1b[0m self)"
+        ));
+
+        assert!(full_text_search(&sources, "::", None, OutputFormat::Plain, false).is_err());
+        Ok(())
+    }
+}
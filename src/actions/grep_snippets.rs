@@ -0,0 +1,290 @@
+use crate::helpers::highlight_matches::highlight_matches;
+use crate::helpers::render_json::render_json;
+use crate::models::grep_match_model::{GrepCount, GrepMatch};
+use crate::models::grep_options_model::GrepOptions;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::snippet_source_model::SnippetSource;
+use anyhow::Context;
+use regex::RegexBuilder;
+use std::collections::BTreeMap;
+
+/// Searches the body of the visible snippets of each collection for `pattern`, line by
+/// line. Plain output follows grep: `key:N: line` for a match, `key-N- line` for context and
+/// `--` between groups of lines, with each line prefixed by `collection:` when there is more
+/// than one collection.
+///
+/// A collection that fails to load is skipped with a warning when there are others.
+pub fn grep_snippets(
+    collections: &[(String, Vec<SnippetSource>)],
+    pattern: &str,
+    options: &GrepOptions,
+    lang: Option<&str>,
+    format: OutputFormat,
+    highlight: bool,
+) -> anyhow::Result<String> {
+    let pattern = match options.word_regexp {
+        true => format!(r"\b(?:{})\b", pattern),
+        false => pattern.to_string(),
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .context("Invalid regex")?;
+
+    let mut matches = Vec::new();
+    for (name, sources) in collections {
+        let snippets = match LayeredSnippets::load(sources) {
+            Ok(snippets) => snippets,
+            Err(error) if collections.len() > 1 => {
+                log::warn!("Skipping collection '{}': {:#}", name, error);
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        for (key, snippet, source) in snippets.iter() {
+            if lang.is_some_and(|lang| !snippet.applies_to(lang)) {
+                continue;
+            }
+            let body = snippet.body_text();
+            let lines: Vec<&str> = body.lines().collect();
+            for (index, line) in lines.iter().enumerate() {
+                let ranges: Vec<(usize, usize)> = regex
+                    .find_iter(line)
+                    .map(|found| (found.start(), found.end()))
+                    .collect();
+                if ranges.is_empty() {
+                    continue;
+                }
+                let after_end = (index + 1 + options.after()).min(lines.len());
+                matches.push(GrepMatch {
+                    collection: name.clone(),
+                    key: key.to_string(),
+                    layer: source.layer,
+                    source: source.path.clone(),
+                    line: index + 1,
+                    text: line.to_string(),
+                    ranges,
+                    before: to_strings(&lines[index.saturating_sub(options.before())..index]),
+                    after: to_strings(&lines[index + 1..after_end]),
+                });
+            }
+        }
+    }
+
+    let name = |collection: &str, key: &str| match collections.len() > 1 {
+        true => format!("{}:{}", collection, key),
+        false => key.to_string(),
+    };
+    let snippets = matches.chunk_by(|a, b| a.collection == b.collection && a.key == b.key);
+    if options.keys_only || options.count {
+        let counts: Vec<GrepCount> = snippets
+            .map(|group| GrepCount {
+                collection: group[0].collection.clone(),
+                key: group[0].key.clone(),
+                count: group.len(),
+            })
+            .collect();
+        return match format {
+            OutputFormat::Json | OutputFormat::Jsonl => render_json(&counts, format),
+            OutputFormat::Table | OutputFormat::Plain => Ok(counts
+                .iter()
+                .map(|count| {
+                    let name = name(&count.collection, &count.key);
+                    match options.count {
+                        true => format!("{}:{}", name, count.count),
+                        false => name,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+        };
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => render_json(&matches, format),
+        OutputFormat::Table | OutputFormat::Plain => {
+            let context = options.before() > 0 || options.after() > 0;
+            let mut out: Vec<String> = Vec::new();
+            for group in snippets {
+                // Line number to its text and, for matches, the ranges matched
+                let mut lines = BTreeMap::new();
+                for found in group {
+                    let first = found.line - found.before.len();
+                    let before = found
+                        .before
+                        .iter()
+                        .enumerate()
+                        .map(|(i, text)| (first + i, text));
+                    let after = found.after.iter().enumerate();
+                    for (number, text) in
+                        before.chain(after.map(|(i, text)| (found.line + 1 + i, text)))
+                    {
+                        lines.entry(number).or_insert((text, None));
+                    }
+                    lines.insert(found.line, (&found.text, Some(&found.ranges)));
+                }
+                let name = name(&group[0].collection, &group[0].key);
+                let mut last = None;
+                for (number, (text, ranges)) in lines {
+                    if context && !out.is_empty() && last.is_none_or(|last| number > last + 1) {
+                        out.push("--".to_string());
+                    }
+                    last = Some(number);
+                    let line = match (ranges, highlight) {
+                        (Some(ranges), true) => {
+                            highlight_matches(text, &char_positions(text, ranges))
+                        }
+                        _ => text.to_string(),
+                    };
+                    let separator = if ranges.is_some() { ':' } else { '-' };
+                    out.push(format!("{name}{separator}{number}{separator} {line}"));
+                }
+            }
+            Ok(out.join("\n"))
+        }
+    }
+}
+
+fn to_strings(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+/// Indices of the chars of `text` inside the byte `ranges`.
+fn char_positions(text: &str, ranges: &[(usize, usize)]) -> Vec<usize> {
+    text.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, _))| {
+            ranges
+                .iter()
+                .any(|&(start, end)| (start..end).contains(byte))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    fn snippet_file() -> anyhow::Result<NamedTempFile> {
+        let test_file = NamedTempFile::new()?;
+        fs::write(
+            test_file.path(),
+            r#"{
+  "deref": { "prefix": "deref", "body": ["impl std::ops::Deref for $1 {", "    type Target = $2;", "", "    fn deref(&self) -> &$2 {", "    }", "}"] },
+  "clone": { "prefix": "cl", "body": "impl Clone for $1 {}" }
+}"#,
+        )?;
+        Ok(test_file)
+    }
+
+    fn collections(file: &NamedTempFile) -> Vec<(String, Vec<SnippetSource>)> {
+        let sources = vec![SnippetSource::user(file.path().to_str().unwrap())];
+        vec![("rust".to_string(), sources)]
+    }
+
+    #[test]
+    fn test_grep_snippets() -> anyhow::Result<()> {
+        let file = snippet_file()?;
+        let collections = collections(&file);
+        let grep = |pattern: &str, options: &GrepOptions, highlight: bool| {
+            grep_snippets(
+                &collections,
+                pattern,
+                options,
+                None,
+                OutputFormat::Plain,
+                highlight,
+            )
+        };
+
+        let options = GrepOptions::default();
+        assert_eq!(
+            grep("impl", &options, false)?,
+            "deref:1: impl std::ops::Deref for $1 {\nclone:1: impl Clone for $1 {}"
+        );
+        assert_eq!(
+            grep("Deref", &options, true)?,
+            "deref:1: impl std::ops::\x1b[1;33mDeref\x1b[0m for $1 {"
+        );
+
+        // Context lines of nearby matches merge, groups apart are separated
+        let options = GrepOptions {
+            before_context: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep(r"Target|fn|Clone", &options, false)?,
+            "deref-1- impl std::ops::Deref for $1 {\nderef:2:     type Target = $2;\nderef-3- \nderef:4:     fn deref(&self) -> &$2 {\n--\nclone:1: impl Clone for $1 {}"
+        );
+
+        let options = GrepOptions {
+            ignore_case: true,
+            word_regexp: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep("DEREF", &options, false)?,
+            "deref:1: impl std::ops::Deref for $1 {\nderef:4:     fn deref(&self) -> &$2 {"
+        );
+        assert_eq!(grep("DERE", &options, false)?, "");
+
+        assert!(grep("(", &GrepOptions::default(), false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_grep_snippets_counts_and_json() -> anyhow::Result<()> {
+        let file = snippet_file()?;
+        let collections = collections(&file);
+        let count = GrepOptions {
+            count: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_snippets(
+                &collections,
+                r"\$\d",
+                &count,
+                None,
+                OutputFormat::Plain,
+                false
+            )?,
+            "deref:3\nclone:1"
+        );
+        let keys_only = GrepOptions {
+            keys_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_snippets(
+                &collections,
+                "fn",
+                &keys_only,
+                None,
+                OutputFormat::Plain,
+                false
+            )?,
+            "deref"
+        );
+
+        let output = grep_snippets(
+            &collections,
+            "Target",
+            &GrepOptions::default(),
+            None,
+            OutputFormat::Json,
+            false,
+        )?;
+        let found: serde_json::Value = serde_json::from_str(&output)?;
+        assert_eq!(found[0]["key"], "deref");
+        assert_eq!(found[0]["collection"], "rust");
+        assert_eq!(found[0]["layer"], "user");
+        assert_eq!(found[0]["line"], 2);
+        assert_eq!(found[0]["ranges"], serde_json::json!([[9, 15]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::helpers::jsonc::line_col;
+use crate::models::expand_context_model::ExpandContext;
+use crate::models::expansion_model::CursorRecord;
+use crate::models::insert_position_model::InsertPosition;
+use crate::models::insert_record_model::InsertRecord;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::snippet_source_model::SnippetSource;
+use crate::parser::render_snippet_ast::render_snippet_ast;
+use crate::parser::snippet_ast::SnippetAst;
+use anyhow::Context;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Renders the snippet with the key or prefix `name` and splices it into `file_path`.
+///
+/// Every non-empty line of the snippet is indented like the line at the insertion point, or
+/// the nearest non-blank line above it when that one is blank. Before a closing bracket the
+/// snippet is indented one level deeper, so it lands inside the block. The snippet's own
+/// indentation is converted to the tabs or spaces the file uses. The file keeps its line
+/// endings and is replaced atomically.
+pub fn insert_snippet(
+    sources: &[SnippetSource],
+    name: &str,
+    file_path: &Path,
+    position: &InsertPosition,
+    mut context: ExpandContext,
+    fill: impl FnOnce(&SnippetAst, &mut ExpandContext) -> anyhow::Result<()>,
+) -> anyhow::Result<InsertRecord> {
+    let snippets = LayeredSnippets::load(sources)?;
+    let (key, snippet, _) = snippets
+        .find(name)
+        .ok_or_else(|| anyhow::anyhow!("No snippet with key or prefix '{}' found", name))?;
+
+    let _lock = FileLock::acquire(file_path)?;
+    let text = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    let lines: Vec<&str> = text.lines().collect();
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let index = match position {
+        InsertPosition::Line(line) if (1..=lines.len() + 1).contains(line) => line - 1,
+        InsertPosition::Line(line) => {
+            return Err(anyhow::anyhow!(
+                "Line {} is outside {}, which has {} lines",
+                line,
+                file_path.display(),
+                lines.len()
+            ))
+        }
+        InsertPosition::AfterPattern(pattern) => {
+            let regex = Regex::new(pattern).context("Invalid pattern")?;
+            lines
+                .iter()
+                .position(|line| regex.is_match(line))
+                .map(|found| found + 1)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("No line of {} matches '{}'", file_path.display(), pattern)
+                })?
+        }
+    };
+    let mut indent = lines
+        .get(index)
+        .into_iter()
+        .chain(lines[..index].iter().rev())
+        .find(|line| !line.trim().is_empty())
+        .map_or(String::new(), |line| indentation(line).to_string());
+    let file_unit = indent_unit(lines.iter().copied());
+    // Before the closing bracket of a block the snippet goes inside it, one level deeper
+    if let Some(line) = lines.get(index) {
+        if line.trim_start().starts_with(['}', ')', ']']) {
+            indent.push_str(file_unit.as_deref().unwrap_or("    "));
+        }
+    }
+
+    let ast = snippet.body_ast();
+    fill(&ast, &mut context)?;
+    let expansion = render_snippet_ast(&ast, &context);
+
+    let mut out = String::new();
+    for line in &lines[..index] {
+        out.push_str(line);
+        out.push_str(newline);
+    }
+    // The snippet's own indentation is rewritten in the file's unit, tabs or spaces
+    let body_unit = indent_unit(expansion.text.split('\n'));
+    let mut cursor = None;
+    let mut start = 0;
+    for line in expansion.text.split('\n') {
+        if !line.is_empty() {
+            out.push_str(&indent);
+        }
+        let end = start + line.len();
+        let converted = match (&body_unit, &file_unit) {
+            (Some(from), Some(to)) if from != to => reindent(line, from, to),
+            _ => line.to_string(),
+        };
+        if cursor.is_none() && (start..=end).contains(&expansion.cursor) {
+            let (old, new) = (indentation(line).len(), indentation(&converted).len());
+            let column = match expansion.cursor - start {
+                column if column >= old => column - old + new,
+                column => column.min(new),
+            };
+            cursor = Some(out.len() + column);
+        }
+        out.push_str(&converted);
+        out.push_str(newline);
+        start = end + 1;
+    }
+    for line in &lines[index..] {
+        out.push_str(line);
+        out.push_str(newline);
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.truncate(out.len() - newline.len());
+    }
+
+    atomic_write(file_path, &out)
+        .with_context(|| format!("Failed to write {}", file_path.display()))?;
+
+    let offset = cursor.unwrap_or(out.len());
+    let (line, column) = line_col(&out, offset);
+    Ok(InsertRecord {
+        key: key.to_string(),
+        path: file_path.to_string_lossy().into_owned(),
+        line: index + 1,
+        lines: expansion.text.split('\n').count(),
+        cursor: CursorRecord {
+            offset,
+            line,
+            column,
+        },
+    })
+}
+
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// One level of indentation of `lines`: the most common step in from one non-blank line to
+/// the next, a tab when that step starts with one. ` * ` doc comment lines are left out, as
+/// their single space is alignment.
+fn indent_unit<'a>(lines: impl Iterator<Item = &'a str>) -> Option<String> {
+    let mut steps: BTreeMap<String, usize> = BTreeMap::new();
+    let mut previous = "";
+    for line in lines.filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('*'))
+    {
+        let indent = indentation(line);
+        if let Some(step) = indent
+            .strip_prefix(previous)
+            .filter(|step| !step.is_empty())
+        {
+            let unit = match step.starts_with('\t') {
+                true => "\t",
+                false => step,
+            };
+            *steps.entry(unit.to_string()).or_default() += 1;
+        }
+        previous = indent;
+    }
+    // On a tie the shorter step is the unit
+    steps
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.len().cmp(&a.len())))
+        .map(|(unit, _)| unit)
+}
+
+/// `line` with the levels of its leading indentation written in `to` instead of `from`.
+/// Spaces short of a level are kept for alignment.
+fn reindent(line: &str, from: &str, to: &str) -> String {
+    let leading = indentation(line);
+    let tabs = leading.matches('\t').count();
+    let mut spaces = leading.len() - tabs;
+    let mut levels = tabs;
+    if from != "\t" {
+        levels += spaces / from.len();
+        spaces %= from.len();
+    }
+    format!(
+        "{}{}{}",
+        to.repeat(levels),
+        " ".repeat(spaces),
+        &line[leading.len()..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup(source: &str) -> anyhow::Result<(TempDir, Vec<SnippetSource>)> {
+        let dir = TempDir::new()?;
+        let snippets = dir.path().join("rust.json");
+        fs::write(
+            &snippets,
+            r#"{
+  "getter": { "prefix": "get", "body": ["pub fn ${1:name}(&self) -> &${2:T} {", "\t&self.$1$0", "}", ""] },
+  "check": { "prefix": "chk", "body": ["if ${1:ok} {", "    $0todo!()", "}"] }
+}"#,
+        )?;
+        fs::write(dir.path().join("lib.rs"), source)?;
+        let sources = vec![SnippetSource::user(snippets.to_str().unwrap())];
+        Ok((dir, sources))
+    }
+
+    #[test]
+    fn test_insert_after_pattern_with_indentation() -> anyhow::Result<()> {
+        let (dir, sources) = setup("mod a {\r\n    impl Foo {\r\n    }\r\n}\r\n")?;
+        let file = dir.path().join("lib.rs");
+
+        let record = insert_snippet(
+            &sources,
+            "get",
+            &file,
+            &InsertPosition::AfterPattern("impl Foo".to_string()),
+            ExpandContext::default(),
+            |_, _| Ok(()),
+        )?;
+
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "mod a {\r\n    impl Foo {\r\n        pub fn name(&self) -> &T {\r\n            &self.name\r\n        }\r\n\r\n    }\r\n}\r\n"
+        );
+        assert_eq!((record.line, record.lines), (3, 4));
+        assert_eq!((record.cursor.line, record.cursor.column), (4, 23));
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_under_tab_indented_line() -> anyhow::Result<()> {
+        let (dir, sources) = setup("fn main() {\n\tlet ok = true;\n}\n")?;
+        let file = dir.path().join("lib.rs");
+
+        let record = insert_snippet(
+            &sources,
+            "chk",
+            &file,
+            &InsertPosition::AfterPattern("let ok".to_string()),
+            ExpandContext::default(),
+            |_, _| Ok(()),
+        )?;
+
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "fn main() {\n\tlet ok = true;\n\tif ok {\n\t\ttodo!()\n\t}\n}\n"
+        );
+        assert_eq!((record.cursor.line, record.cursor.column), (4, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_below_doc_comment() -> anyhow::Result<()> {
+        let (dir, sources) =
+            setup("/**\n * Entry point.\n */\nfn main() {\n    let ok = true;\n}\n")?;
+        let file = dir.path().join("lib.rs");
+
+        let record = insert_snippet(
+            &sources,
+            "chk",
+            &file,
+            &InsertPosition::AfterPattern("let ok".to_string()),
+            ExpandContext::default(),
+            |_, _| Ok(()),
+        )?;
+
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "/**\n * Entry point.\n */\nfn main() {\n    let ok = true;\n    if ok {\n        todo!()\n    }\n}\n"
+        );
+        assert_eq!((record.cursor.line, record.cursor.column), (7, 9));
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_at_line() -> anyhow::Result<()> {
+        let (dir, sources) = setup("struct Foo;\n\nfn main() {}")?;
+        let file = dir.path().join("lib.rs");
+        let insert = |line| {
+            insert_snippet(
+                &sources,
+                "getter",
+                &file,
+                &InsertPosition::Line(line),
+                ExpandContext::default(),
+                |_, _| Ok(()),
+            )
+        };
+
+        assert!(insert(5).is_err());
+        insert(4)?;
+
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "struct Foo;\n\nfn main() {}\npub fn name(&self) -> &T {\n\t&self.name\n}\n"
+        );
+        Ok(())
+    }
+}
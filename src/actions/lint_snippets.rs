@@ -0,0 +1,130 @@
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::helpers::fix_snippet::fix_snippet;
+use crate::helpers::lint_snippet_document::lint_snippet_document;
+use crate::models::lint_finding_model::LintReport;
+use crate::models::lint_rule_model::LintLevels;
+use crate::models::snippet_change_model::{Operation, SnippetChange, SnippetEntry};
+use crate::models::snippet_document_model::SnippetDocument;
+use anyhow::Context;
+use std::fs;
+use std::path::Path;
+
+/// Lints collection files, missing or empty ones are skipped.
+///
+/// With `fix`, the fixable problems are fixed first and each fixed snippet is returned as a
+/// change, so it can be journaled. The findings are the ones left afterwards.
+pub fn lint_snippets(
+    paths: &[String],
+    levels: &LintLevels,
+    fix: bool,
+) -> anyhow::Result<LintReport> {
+    let mut report = LintReport::default();
+    for file_path in paths {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            log::info!("Skipping {}, it doesn't exist", file_path);
+            continue;
+        }
+        let _lock = fix.then(|| FileLock::acquire(path)).transpose()?;
+        let file_contents =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", file_path))?;
+        let mut document = SnippetDocument::parse(file_contents)
+            .with_context(|| format!("Failed to parse {}", file_path))?;
+        let mut findings = lint_snippet_document(file_path, &document, levels);
+
+        if fix && findings.iter().any(|finding| finding.fixable) {
+            let fixes: Vec<_> = document
+                .iter()
+                .filter_map(|(key, snippet)| {
+                    let rules: Vec<_> = findings
+                        .iter()
+                        .filter(|finding| finding.fixable && finding.key == key)
+                        .map(|finding| finding.rule)
+                        .collect();
+                    let fixed = fix_snippet(snippet, &rules);
+                    (fixed != *snippet).then(|| (key.to_string(), snippet.clone(), fixed))
+                })
+                .collect();
+            for (key, snippet, fixed) in fixes {
+                let before_text = document.text().to_string();
+                document.replace(&key, &fixed)?;
+                report.changes.push(SnippetChange::new(
+                    file_path,
+                    Operation::Edit,
+                    Some(SnippetEntry::new(&key, &snippet)),
+                    Some(SnippetEntry::new(&key, &fixed)),
+                    &before_text,
+                    document.text(),
+                ));
+            }
+            atomic_write(path, document.text())
+                .with_context(|| format!("Failed to write {}", file_path))?;
+            findings = lint_snippet_document(file_path, &document, levels);
+        }
+        report.findings.extend(findings);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::lint_rule_model::{LintLevel, LintRule};
+    use tempfile::{tempdir, NamedTempFile};
+
+    #[test]
+    fn test_lint_snippets_with_fix() -> anyhow::Result<()> {
+        let test_file = NamedTempFile::new()?;
+        fs::write(
+            test_file.path(),
+            "{\n  // Greeting\n  \"hi\": {\n    \"prefix\": \"hi\",\n    \"body\": [\n      \"echo $ hi \",\n      \"$FOO\"\n    ]\n  }\n}\n",
+        )?;
+        let paths = [test_file.path().to_str().unwrap().to_string()];
+        let mut levels = LintLevels::default();
+        levels.set(&[LintRule::EmptyDescription], LintLevel::Allow);
+        levels.set(&[LintRule::UnknownVariable], LintLevel::Deny);
+
+        let report = lint_snippets(&paths, &levels, false)?;
+        assert_eq!(report.findings.len(), 3);
+        assert_eq!(report.errors(), 1);
+
+        let report = lint_snippets(&paths, &levels, true)?;
+        assert_eq!(report.changes.len(), 1);
+        let rules: Vec<_> = report.findings.iter().map(|f| f.rule).collect();
+        assert_eq!(rules, vec![LintRule::UnknownVariable]);
+        assert_eq!(
+            fs::read_to_string(test_file.path())?,
+            "{\n  // Greeting\n  \"hi\": {\n    \"prefix\": \"hi\",\n    \"body\": [\n      \"echo \\\\$ hi\",\n      \"$FOO\"\n    ]\n  }\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_fix_inline_snippet() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("sub"))?;
+        let path = dir.path().join("rust.json");
+        fs::write(
+            &path,
+            "{ \"hi\": { \"prefix\": \"hi\", \"body\": [\"echo $ hi\", \"done \"], \"description\": \"Hi\" } }\n",
+        )?;
+        let typed = dir.path().join("sub").join("..").join("rust.json");
+
+        let report = lint_snippets(
+            &[typed.to_str().unwrap().to_string()],
+            &LintLevels::default(),
+            true,
+        )?;
+
+        assert_eq!(
+            report.changes[0].path,
+            fs::canonicalize(&path)?.to_str().unwrap()
+        );
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "{ \"hi\": { \"prefix\": \"hi\", \"body\": [\"echo \\\\$ hi\", \"done\"], \"description\": \"Hi\" } }\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::helpers::render_json::render_json;
+use crate::models::collection_record_model::CollectionRecord;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::snip_config_model::SnipConfig;
+use prettytable::{row, Table};
+
+/// Renders the configured collections, marking the active one.
+pub fn list_collections(config: &SnipConfig, format: OutputFormat) -> anyhow::Result<String> {
+    let records: Vec<CollectionRecord> = config
+        .collections
+        .iter()
+        .map(|(name, collection)| CollectionRecord::new(name, collection, *name == config.active))
+        .collect();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => render_json(&records, format),
+        OutputFormat::Plain => Ok(records
+            .iter()
+            .map(|record| format!("{}\t{}\t{}\n", record.name, record.lang, record.path))
+            .collect()),
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["", "Name", "Language", "Extensions", "Path"]);
+            for record in records {
+                let active = if record.active { "*" } else { "" };
+                table.add_row(row![
+                    active,
+                    record.name,
+                    record.lang,
+                    record.extensions.join(", "),
+                    record.path
+                ]);
+            }
+            Ok(table.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_collections_marks_active() {
+        let mut config = SnipConfig::default_config();
+        config
+            .add_collection(
+                "web".to_string(),
+                "/snippets/ts.json".to_string(),
+                Some("typescript".to_string()),
+                vec!["ts".to_string(), "tsx".to_string()],
+            )
+            .unwrap();
+
+        let output = list_collections(&config, OutputFormat::Table).unwrap();
+        assert!(output.contains("| web "));
+        assert!(output.contains("ts, tsx"));
+        assert!(output.contains(&format!("| * | {}", config.active)));
+
+        let output = list_collections(&config, OutputFormat::Jsonl).unwrap();
+        assert!(output.contains(r#""name":"web","lang":"typescript""#));
+    }
+}
@@ -1,33 +1,45 @@
+use crate::helpers::render_json::render_json;
+use crate::helpers::render_snippet_table::render_snippet_table;
 use crate::models::identifier_model::Identifier;
-use crate::models::snippet_model::Snippet;
-use anyhow::Context;
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::snippet_record_model::SnippetRecord;
+use crate::models::snippet_source_model::SnippetSource;
 
-pub fn list_snippets(file_path: &str, list_option: Identifier) -> anyhow::Result<String> {
-    let path = Path::new(file_path);
+/// Lists the visible snippets. Plain output has one key or prefix per line, followed by the
+/// layer it comes from. With `lang`, only snippets scoped to that language are listed.
+pub fn list_snippets(
+    sources: &[SnippetSource],
+    list_option: Identifier,
+    lang: Option<&str>,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let snippets = LayeredSnippets::load(sources)?;
+    let visible = || {
+        snippets
+            .iter()
+            .filter(|(_, snippet, _)| lang.is_none_or(|lang| snippet.applies_to(lang)))
+    };
+    let records = || -> Vec<SnippetRecord> {
+        visible()
+            .map(|(key, snippet, source)| SnippetRecord::new(key, snippet, source))
+            .collect()
+    };
 
-    let snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
-        let file_contents =
-            fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
-    } else {
-        return Err(anyhow::anyhow!("Snippet file not found or is empty"));
+    let output = match (format, list_option) {
+        (OutputFormat::Json | OutputFormat::Jsonl, _) => render_json(&records(), format)?,
+        (OutputFormat::Table, _) => render_snippet_table(&records()),
+        (OutputFormat::Plain, Identifier::Key) => visible()
+            .map(|(key, _, source)| format!("{}\t{}\n", key, source.layer))
+            .collect(),
+        (OutputFormat::Plain, Identifier::Prefix) => visible()
+            .flat_map(|(_, snippet, source)| {
+                snippet
+                    .prefixes()
+                    .map(move |prefix| format!("{}\t{}\n", prefix, source.layer))
+            })
+            .collect(),
     };
-    let mut output = String::new();
-    match list_option {
-        Identifier::Key => {
-            for key in snippets.keys() {
-                output.push_str(&format!("{}\n", key));
-            }
-        }
-        Identifier::Prefix => {
-            for snippet in snippets.values() {
-                output.push_str(&format!("{}\n", snippet.prefix));
-            }
-        }
-    }
 
     Ok(output)
 }
@@ -35,6 +47,7 @@ pub fn list_snippets(file_path: &str, list_option: Identifier) -> anyhow::Result
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::snippet_model::Snippet;
     use anyhow::Result;
     use std::collections::HashMap;
     use std::fs;
@@ -50,29 +63,71 @@ mod tests {
             (
                 "key1".to_string(),
                 Snippet {
-                    prefix: "prefix1".to_string(),
-                    description: "desc1".to_string(),
-                    body: vec!["body1".to_string()],
+                    prefix: "prefix1".into(),
+                    description: Some("desc1".to_string()),
+                    body: vec!["body1".to_string()].into(),
+                    ..Default::default()
                 },
             ),
             (
                 "key2".to_string(),
                 Snippet {
-                    prefix: "prefix2".to_string(),
-                    description: "desc2".to_string(),
-                    body: vec!["body2".to_string()],
+                    prefix: "prefix2".into(),
+                    description: Some("desc2".to_string()),
+                    body: vec!["body2".to_string()].into(),
+                    ..Default::default()
                 },
             ),
         ]);
         fs::write(test_path, serde_json::to_string(&snippets)?)?;
 
         // Call the list_snippets function
-        let output = list_snippets(test_path, Identifier::Key)?;
+        let output = list_snippets(
+            &[SnippetSource::user(test_path)],
+            Identifier::Key,
+            None,
+            OutputFormat::Plain,
+        )?;
 
         // Assert the results
-        assert!(output.contains("key1"));
+        assert!(output.contains("key1\tuser\n"));
         assert!(output.contains("key2"));
 
         Ok(())
     }
+
+    #[test]
+    fn test_list_snippets_for_lang() -> Result<()> {
+        let test_file = NamedTempFile::new()?;
+        fs::write(
+            test_file.path(),
+            r#"{
+                "fn": { "prefix": "fn", "body": "", "scope": "rust,toml" },
+                "def": { "prefix": "def", "body": "", "scope": "python" },
+                "todo": { "prefix": "todo", "body": "" }
+            }"#,
+        )?;
+        let sources = [SnippetSource::user(test_file.path().to_str().unwrap())];
+
+        let output = list_snippets(
+            &sources,
+            Identifier::Prefix,
+            Some("toml"),
+            OutputFormat::Plain,
+        )?;
+        assert_eq!(output, "fn\tuser\ntodo\tuser\n");
+
+        let output = list_snippets(
+            &sources,
+            Identifier::Prefix,
+            Some("toml"),
+            OutputFormat::Jsonl,
+        )?;
+        let keys: Vec<String> = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["key"].to_string())
+            .collect();
+        assert_eq!(keys, vec!["\"fn\"", "\"todo\""]);
+        Ok(())
+    }
 }
@@ -1,38 +1,48 @@
-use crate::models::snippet_model::Snippet;
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::models::snippet_change_model::{Operation, SnippetChange, SnippetEntry};
+use crate::models::snippet_document_model::SnippetDocument;
 use anyhow::Context;
-use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-pub fn remove_snippet_from_file(file_path: &str, key: &str) -> anyhow::Result<()> {
+pub fn remove_snippet_from_file(file_path: &str, key: &str) -> anyhow::Result<SnippetChange> {
     let path = Path::new(file_path);
 
+    // Hold the lock until the updated file is in place so concurrent runs don't drop changes
+    let _lock = FileLock::acquire(path)?;
+
     // Check if file exists and is not empty
-    let mut snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
+    let mut snippets = if path.exists() && path.metadata()?.len() > 0 {
         let file_contents =
             fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
+        SnippetDocument::parse(file_contents).context("Failed to parse the snippets file")?
     } else {
         return Err(anyhow::anyhow!("No snippet file found or file is empty"));
     };
 
     // Remove the snippet with the given key
-    if snippets.remove(key).is_none() {
-        return Err(anyhow::anyhow!("Snippet with key '{}' not found", key));
-    }
+    let original_text = snippets.text().to_string();
+    let removed = snippets.remove(key)?;
 
-    // Write the updated HashMap back to the JSON file
-    let updated_contents =
-        serde_json::to_string_pretty(&snippets).context("Failed to serialize snippets")?;
-    fs::write(file_path, updated_contents).context("Failed to write to the snippets file")?;
+    // Write the updated document back to the JSON file
+    atomic_write(path, snippets.text()).context("Failed to write to the snippets file")?;
 
-    Ok(())
+    Ok(SnippetChange::new(
+        file_path,
+        Operation::Remove,
+        Some(SnippetEntry::new(key, &removed)),
+        None,
+        &original_text,
+        snippets.text(),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::actions::write_snippet_to_file::write_snippet_to_file;
+    use std::collections::HashMap;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -42,7 +52,7 @@ mod tests {
 
         let existing_key = "existing_key".to_string();
         let non_existing_key = "non_existing_key".to_string();
-        let test_prefix = "test_prefix".to_string();
+        let test_prefix = vec!["test_prefix".to_string()];
         let test_description = "test_description".to_string();
         let test_body = vec!["test_body".to_string()];
 
@@ -52,6 +62,7 @@ mod tests {
             existing_key,
             test_prefix,
             test_description,
+            None,
             test_body,
         );
         assert!(write_result.is_ok());
@@ -70,4 +81,26 @@ mod tests {
 
         // No need for explicit cleanup, as NamedTempFile automatically deletes the file
     }
+
+    #[test]
+    fn test_remove_snippet_keeps_unknown_fields_of_others() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let test_path = temp_file.path().to_str().unwrap();
+
+        fs::write(
+            test_path,
+            r#"{
+  "removed": { "prefix": "rm", "body": ["rm"] },
+  "kept": { "prefix": "kp", "body": ["kp"], "scope": "rust", "luasnip": { "priority": 10 } }
+}"#,
+        )
+        .unwrap();
+
+        remove_snippet_from_file(test_path, "removed").unwrap();
+
+        let contents = fs::read_to_string(test_path).unwrap();
+        let snippets: HashMap<String, serde_json::Value> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(snippets["kept"]["scope"], "rust");
+        assert_eq!(snippets["kept"]["luasnip"]["priority"], 10);
+    }
 }
@@ -0,0 +1,171 @@
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::helpers::hash_contents::hash_contents;
+use crate::models::journal_model::{Journal, JournalEntry};
+use crate::models::snippet_document_model::SnippetDocument;
+use anyhow::Context;
+use std::fs;
+use std::path::Path;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Replay {
+    Undo,
+    Redo,
+}
+
+/// Undoes the last applied journal entry or redoes the last undone one.
+///
+/// Refuses when the collection file was changed outside `snip` since the entry was recorded,
+/// as replaying it could then overwrite those changes.
+pub fn replay_change(journal_path: &Path, replay: Replay) -> anyhow::Result<JournalEntry> {
+    let _journal_lock = FileLock::acquire(journal_path)?;
+    let mut journal = Journal::load(journal_path)?;
+
+    let index = match replay {
+        Replay::Undo => journal.undo_index(),
+        Replay::Redo => journal.redo_index(),
+    }
+    .ok_or_else(|| match replay {
+        Replay::Undo => anyhow::anyhow!("Nothing to undo"),
+        Replay::Redo => anyhow::anyhow!("Nothing to redo"),
+    })?;
+    let entry = journal.entries[index].clone();
+    let change = &entry.change;
+    let expected_hash = match replay {
+        Replay::Undo => &change.after_hash,
+        Replay::Redo => &change.before_hash,
+    };
+
+    let path = Path::new(&change.path);
+    let _lock = FileLock::acquire(path)?;
+    let file_contents = if path.exists() {
+        fs::read_to_string(path).context("Failed to read the snippets file")?
+    } else {
+        String::new()
+    };
+    if hash_contents(&file_contents) != *expected_hash {
+        return Err(anyhow::anyhow!(
+            "{} has changed outside snip since change #{} was recorded, refusing to {}",
+            change.path,
+            entry.id,
+            match replay {
+                Replay::Undo => "undo it",
+                Replay::Redo => "redo it",
+            }
+        ));
+    }
+
+    let mut snippets =
+        SnippetDocument::parse(file_contents).context("Failed to parse the snippets file")?;
+    match replay {
+        Replay::Undo => change.revert(&mut snippets)?,
+        Replay::Redo => change.apply(&mut snippets)?,
+    }
+    atomic_write(path, snippets.text()).context("Failed to write to the snippets file")?;
+
+    let hash = hash_contents(snippets.text());
+    match replay {
+        Replay::Undo => journal.mark_undone(index, hash),
+        Replay::Redo => journal.mark_redone(index, hash),
+    }
+    journal.write(journal_path)?;
+
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::edit_snippet_in_file::edit_snippet_in_file;
+    use crate::actions::remove_snippet_from_file::remove_snippet_from_file;
+    use crate::actions::update_key_in_file::update_key_in_file;
+    use crate::actions::write_snippet_to_file::write_snippet_to_file;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_undo_and_redo() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let journal_path = temp_dir.path().join("journal.json");
+        let snippets_path = temp_dir.path().join("rust.json");
+        let file_path = snippets_path.to_str().unwrap();
+
+        let change = write_snippet_to_file(
+            file_path,
+            "key".to_string(),
+            vec!["prefix".to_string()],
+            "description".to_string(),
+            None,
+            vec!["body".to_string()],
+        )?;
+        Journal::record(&journal_path, change)?;
+        let added = fs::read_to_string(file_path)?;
+        let change = edit_snippet_in_file(
+            file_path,
+            "key".to_string(),
+            None,
+            None,
+            None,
+            Some(vec!["new body".to_string()]),
+        )?;
+        Journal::record(&journal_path, change)?;
+        let edited = fs::read_to_string(file_path)?;
+        Journal::record(&journal_path, remove_snippet_from_file(file_path, "key")?)?;
+
+        replay_change(&journal_path, Replay::Undo)?;
+        assert_eq!(fs::read_to_string(file_path)?, edited);
+        replay_change(&journal_path, Replay::Undo)?;
+        assert_eq!(fs::read_to_string(file_path)?, added);
+
+        let entry = replay_change(&journal_path, Replay::Redo)?;
+        assert_eq!(entry.id, 2);
+        assert_eq!(fs::read_to_string(file_path)?, edited);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_restores_exact_bytes() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let journal_path = temp_dir.path().join("journal.json");
+        let snippets_path = temp_dir.path().join("rust.json");
+        let file_path = snippets_path.to_str().unwrap();
+        let text = "{\n  \"a\": { \"prefix\": \"a\", \"body\": \"x\" },\n  \"m\": {\"prefix\":[\"m\",\"mm\"],\"body\":\"y\"}\n}\n";
+        fs::write(file_path, text)?;
+
+        Journal::record(&journal_path, remove_snippet_from_file(file_path, "a")?)?;
+        replay_change(&journal_path, Replay::Undo)?;
+        assert_eq!(fs::read_to_string(file_path)?, text);
+
+        let change = update_key_in_file(file_path, "m".to_string(), "n".to_string())?;
+        Journal::record(&journal_path, change)?;
+        replay_change(&journal_path, Replay::Undo)?;
+        assert_eq!(fs::read_to_string(file_path)?, text);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_refuses_when_file_changed_outside_snip() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let journal_path = temp_dir.path().join("journal.json");
+        let snippets_path = temp_dir.path().join("rust.json");
+        let file_path = snippets_path.to_str().unwrap();
+
+        let change = write_snippet_to_file(
+            file_path,
+            "key".to_string(),
+            vec!["prefix".to_string()],
+            "description".to_string(),
+            None,
+            vec!["body".to_string()],
+        )?;
+        Journal::record(&journal_path, change)?;
+        fs::write(file_path, "{}")?;
+
+        let error = replay_change(&journal_path, Replay::Undo).unwrap_err();
+        assert!(error.to_string().contains("has changed outside snip"));
+        assert_eq!(fs::read_to_string(file_path)?, "{}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use crate::actions::copy_snippet_to_file::copy_snippet_to_file;
+use crate::actions::edit_snippet_interactively::edit_snippet_interactively;
+use crate::actions::remove_snippet_from_file::remove_snippet_from_file;
+use crate::actions::update_key_in_file::update_key_in_file;
+use crate::helpers::draw_tui::draw_tui;
+use crate::models::collection_model::Collection;
+use crate::models::expand_context_model::ExpandContext;
+use crate::models::journal_model::Journal;
+use crate::models::snippet_template_model::SnippetTemplate;
+use crate::models::tui_state_model::{TuiAction, TuiState};
+use crate::parser::render_snippet_ast::render_snippet_ast;
+use anyhow::Context;
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::Event;
+use ratatui::Terminal;
+use std::io;
+use std::path::Path;
+
+/// Runs the snippet browser until it is quit or `events` ends. Changes go through the same
+/// actions as the commands, to the file each snippet comes from, and are journaled.
+///
+/// `edit` opens a snippet template in an editor and `copy` puts text on the clipboard, so
+/// both can be replaced when there is no terminal.
+pub fn run_tui<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut TuiState,
+    events: impl IntoIterator<Item = io::Result<Event>>,
+    journal_path: &Path,
+    mut edit: impl FnMut(&SnippetTemplate, &Collection) -> anyhow::Result<SnippetTemplate>,
+    mut copy: impl FnMut(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut events = events.into_iter();
+    loop {
+        terminal.draw(|frame| draw_tui(frame, state))?;
+        let Some(event) = events.next() else {
+            return Ok(());
+        };
+        let Event::Key(key) = event.context("Failed to read from the terminal")? else {
+            continue;
+        };
+        let action = match state.handle_key(key) {
+            Some(TuiAction::Quit) => return Ok(()),
+            Some(action) => action,
+            None => continue,
+        };
+        let result = apply_action(state, &action, journal_path, &mut edit, &mut copy);
+        if action == TuiAction::Edit {
+            // The editor drew over the screen
+            terminal.clear()?;
+        }
+        state.status = match result {
+            Ok(message) => Some(message),
+            Err(error) => Some(format!("Error: {:#}", error)),
+        };
+    }
+}
+
+fn apply_action(
+    state: &mut TuiState,
+    action: &TuiAction,
+    journal_path: &Path,
+    edit: &mut impl FnMut(&SnippetTemplate, &Collection) -> anyhow::Result<SnippetTemplate>,
+    copy: &mut impl FnMut(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<String> {
+    if *action == TuiAction::Reload {
+        state.reload()?;
+        return Ok(format!("Collection '{}'", state.collection().name));
+    }
+    let entry = state
+        .selected_entry()
+        .cloned()
+        .context("No snippet selected")?;
+    let path = entry.source.path.as_str();
+    let key = entry.key.clone();
+
+    let message = match action {
+        TuiAction::Copy => {
+            let context = ExpandContext {
+                lang: Some(state.collection().collection.lang.clone()),
+                ..Default::default()
+            };
+            let expansion = render_snippet_ast(&entry.snippet.body_ast(), &context);
+            copy(&expansion.text)?;
+            return Ok(format!("Copied '{}' to the clipboard", key));
+        }
+        TuiAction::Edit => {
+            let collection = state.collection().collection.clone();
+            let changes = edit_snippet_interactively(path, &key, &entry.snippet, false, |t| {
+                edit(t, &collection)
+            })?;
+            let Some(new_key) = changes.last().map(|change| change.key().to_string()) else {
+                return Ok("No changes made.".to_string());
+            };
+            for change in changes {
+                Journal::record(journal_path, change)?;
+            }
+            state.reload()?;
+            state.select_key(&new_key);
+            format!("Saved '{}'", new_key)
+        }
+        TuiAction::Rename(new_key) => {
+            let change = update_key_in_file(path, key.clone(), new_key.clone())?;
+            Journal::record(journal_path, change)?;
+            state.reload()?;
+            state.select_key(new_key);
+            format!("Renamed '{}' to '{}'", key, new_key)
+        }
+        TuiAction::Duplicate(new_key) => {
+            let change = copy_snippet_to_file(path, new_key, &entry.snippet)?;
+            Journal::record(journal_path, change)?;
+            state.reload()?;
+            state.select_key(new_key);
+            format!("Duplicated '{}' as '{}'", key, new_key)
+        }
+        TuiAction::Delete => {
+            let change = remove_snippet_from_file(path, &key)?;
+            Journal::record(journal_path, change)?;
+            state.reload()?;
+            format!("Deleted '{}'", key)
+        }
+        TuiAction::Quit | TuiAction::Reload => unreachable!(),
+    };
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::snippet_source_model::SnippetSource;
+    use crate::models::tui_state_model::TuiCollection;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn keys(text: &str) -> Vec<io::Result<Event>> {
+        text.chars()
+            .map(|c| Ok(Event::Key(KeyEvent::from(KeyCode::Char(c)))))
+            .collect()
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Vec<io::Result<Event>> {
+        vec![Ok(Event::Key(KeyEvent::new(code, modifiers)))]
+    }
+
+    fn backspaces(count: usize) -> Vec<io::Result<Event>> {
+        (0..count)
+            .flat_map(|_| key(KeyCode::Backspace, KeyModifiers::NONE))
+            .collect()
+    }
+
+    #[test]
+    fn test_run_tui() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("rust.json");
+        fs::write(
+            &path,
+            r##"{
+  "hello": { "prefix": "hi", "body": ["println!(\"${1:hello}\");"], "description": "Say hello" },
+  "test": { "prefix": "tst", "body": ["#[test]"], "description": "Test" }
+}"##,
+        )?;
+        let journal_path = dir.path().join("journal.json");
+        let collection = Collection::from_path(path.to_str().unwrap().to_string());
+        let mut state = TuiState::new(
+            vec![TuiCollection {
+                name: "rust".to_string(),
+                sources: vec![SnippetSource::user(&collection.path)],
+                collection,
+            }],
+            0,
+        );
+
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        let events = [
+            // Rename `hello` to `greet`
+            keys("hel"),
+            key(KeyCode::Char('r'), ctrl),
+            backspaces(5),
+            keys("greet"),
+            key(KeyCode::Enter, none),
+            // Change its description in the "editor"
+            key(KeyCode::Char('e'), ctrl),
+            // Copy its expansion
+            key(KeyCode::Enter, none),
+            // Delete `test`
+            backspaces(3),
+            keys("tst"),
+            key(KeyCode::Char('x'), ctrl),
+            keys("y"),
+        ]
+        .into_iter()
+        .flatten();
+
+        let mut copied = Vec::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 12))?;
+        run_tui(
+            &mut terminal,
+            &mut state,
+            events,
+            &journal_path,
+            |template, _| {
+                Ok(SnippetTemplate {
+                    description: "Greet".to_string(),
+                    ..template.clone()
+                })
+            },
+            |text| {
+                copied.push(text.to_string());
+                Ok(())
+            },
+        )?;
+
+        assert_eq!(copied, vec!["println!(\"hello\");"]);
+        assert_eq!(state.status.as_deref(), Some("Deleted 'test'"));
+        let snippets: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        assert_eq!(
+            snippets,
+            serde_json::json!({
+                "greet": { "prefix": "hi", "body": ["println!(\"${1:hello}\");"], "description": "Greet" }
+            })
+        );
+        assert_eq!(Journal::load(&journal_path)?.entries.len(), 3);
+        Ok(())
+    }
+}
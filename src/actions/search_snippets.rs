@@ -1,63 +1,104 @@
-use crate::helpers::is_fuzzy_match;
+use crate::helpers::fuzzy_match::fuzzy_match;
+use crate::helpers::highlight_matches::highlight_matches;
+use crate::helpers::render_json::render_json;
+use crate::helpers::render_snippet_table::render_snippet_table;
+use crate::models::fuzzy_match_model::FuzzyMatch;
 use crate::models::identifier_model::Identifier;
-use crate::models::snippet_model::Snippet;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::snippet_record_model::SnippetRecord;
+use crate::models::snippet_source_model::SnippetSource;
+use crate::parser::parse_query::parse_query;
 use anyhow::Context;
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::cmp::Reverse;
 
+/// Finds the visible snippets whose key or prefix fuzzy matches `name`, best matches first,
+/// or without `id` the ones matching `name` as a query, see `parse_query`. Plain output has
+/// one matching key or prefix per line, followed by the layer it comes from, the other
+/// formats one entry per snippet. With `lang`, only snippets scoped to that language are
+/// searched.
+///
+/// With `highlight`, the matched chars of keys and prefixes are marked with ANSI colors.
 pub fn search_snippets(
-    file_path: &str,
+    sources: &[SnippetSource],
     id: Option<Identifier>,
     name: String,
-) -> anyhow::Result<Vec<String>> {
-    let path = Path::new(file_path);
-
-    let snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
-        let file_contents =
-            fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
-    } else {
-        return Err(anyhow::anyhow!("Snippet file not found or is empty"));
+    lang: Option<&str>,
+    format: OutputFormat,
+    highlight: bool,
+) -> anyhow::Result<String> {
+    let query = match id {
+        Some(_) => None,
+        None => Some(parse_query(&name).context("Invalid query")?),
     };
+    let snippets = LayeredSnippets::load(sources)?;
 
-    let mut results = Vec::new();
-
-    match id {
-        Some(Identifier::Key) => {
-            for (key, _) in snippets.iter() {
-                if is_fuzzy_match::is_fuzzy_match(key, &name) {
-                    results.push(key.clone());
-                }
-            }
+    // The matched key or prefix with the snippet it belongs to
+    let mut matches: Vec<(&str, FuzzyMatch, SnippetRecord)> = Vec::new();
+    for (key, snippet, source) in snippets.iter() {
+        if lang.is_some_and(|lang| !snippet.applies_to(lang)) {
+            continue;
         }
-        Some(Identifier::Prefix) => {
-            for snippet in snippets.values() {
-                if is_fuzzy_match::is_fuzzy_match(&snippet.prefix, &name) {
-                    results.push(snippet.prefix.clone());
-                }
+        let matched: Vec<(&str, FuzzyMatch)> = match id {
+            Some(Identifier::Key) => fuzzy_match(key, &name)
+                .map(|found| (key, found))
+                .into_iter()
+                .collect(),
+            Some(Identifier::Prefix) => snippet
+                .prefixes()
+                .filter_map(|prefix| fuzzy_match(prefix, &name).map(|found| (prefix, found)))
+                .collect(),
+            None if query
+                .as_ref()
+                .is_some_and(|query| query.matches(key, snippet)) =>
+            {
+                vec![(key, FuzzyMatch::default())]
             }
+            None => Vec::new(),
+        };
+        for (label, found) in matched {
+            matches.push((label, found, SnippetRecord::new(key, snippet, source)));
         }
-        None => {
-            for (key, snippet) in snippets.iter() {
-                if snippet
-                    .description
-                    .to_lowercase()
-                    .contains(&name.to_lowercase())
-                {
-                    results.push(key.clone());
-                }
+    }
+    // Equal scores favor the shorter match, then the file order. Query matches keep the
+    // file order.
+    if id.is_some() {
+        matches.sort_by_key(|(label, found, _)| (Reverse(found.score), label.chars().count()));
+    }
+
+    let records = || {
+        let mut records: Vec<SnippetRecord> = Vec::new();
+        for (_, _, record) in &matches {
+            if !records.iter().any(|r| r.key == record.key) {
+                records.push(record.clone());
             }
         }
-    }
+        records
+    };
+    let output = match format {
+        OutputFormat::Json | OutputFormat::Jsonl => render_json(&records(), format)?,
+        OutputFormat::Table => render_snippet_table(&records()),
+        OutputFormat::Plain => matches
+            .iter()
+            .map(|(label, found, record)| {
+                let label = match highlight {
+                    true => highlight_matches(label, &found.positions),
+                    false => label.to_string(),
+                };
+                format!("{}\t{}\n", label, record.layer)
+            })
+            .collect(),
+    };
 
-    Ok(results)
+    Ok(output)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::snippet_model::Snippet;
     use anyhow::Result;
+    use std::collections::HashMap;
     use std::io::{Seek, SeekFrom, Write};
     use tempfile::NamedTempFile;
 
@@ -71,17 +112,19 @@ mod tests {
         test_snippets.insert(
             "test_key1".to_string(),
             Snippet {
-                prefix: "test_prefix1".to_string(),
-                body: vec!["test_body1".to_string()],
-                description: "A test description 1".to_string(),
+                prefix: "test_prefix1".into(),
+                body: vec!["test_body1".to_string()].into(),
+                description: Some("A test description 1".to_string()),
+                ..Default::default()
             },
         );
         test_snippets.insert(
             "test_key2".to_string(),
             Snippet {
-                prefix: "example_prefix2".to_string(),
-                body: vec!["test_body2".to_string()],
-                description: "Another test description 2".to_string(),
+                prefix: "example_prefix2".into(),
+                body: vec!["test_body2".to_string()].into(),
+                description: Some("Another test description 2".to_string()),
+                ..Default::default()
             },
         );
 
@@ -92,21 +135,93 @@ mod tests {
         temp_file.seek(SeekFrom::Start(0))?; // Reset file pointer to start
 
         // Get the file path after writing to the file
-        let file_path = temp_file.path().to_str().unwrap();
+        let file_path = &[SnippetSource::user(temp_file.path().to_str().unwrap())];
 
         // Test search by key
-        let result_key = search_snippets(file_path, Some(Identifier::Key), "key1".to_string())?;
-        assert_eq!(result_key, vec!["test_key1"]);
+        let result_key = search_snippets(
+            file_path,
+            Some(Identifier::Key),
+            "key1".to_string(),
+            None,
+            OutputFormat::Plain,
+            false,
+        )?;
+        assert_eq!(result_key, "test_key1\tuser\n");
 
         // Test search by prefix
-        let result_prefix =
-            search_snippets(file_path, Some(Identifier::Prefix), "example".to_string())?;
-        assert_eq!(result_prefix, vec!["example_prefix2"]);
+        let result_prefix = search_snippets(
+            file_path,
+            Some(Identifier::Prefix),
+            "example".to_string(),
+            None,
+            OutputFormat::Plain,
+            false,
+        )?;
+        assert_eq!(result_prefix, "example_prefix2\tuser\n");
 
         // Test search by description
-        let result_desc = search_snippets(file_path, None, "description 2".to_string())?;
-        assert_eq!(result_desc, vec!["test_key2"]);
+        let result_desc = search_snippets(
+            file_path,
+            None,
+            "description 2".to_string(),
+            None,
+            OutputFormat::Plain,
+            false,
+        )?;
+        assert_eq!(result_desc, "test_key2\tuser\n");
+
+        // Test JSON records of the matched snippets
+        let result_json = search_snippets(
+            file_path,
+            None,
+            "description".to_string(),
+            None,
+            OutputFormat::Json,
+            false,
+        )?;
+        let records: Vec<serde_json::Value> = serde_json::from_str(&result_json)?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["source"], file_path[0].path.as_str());
 
         Ok(())
     }
+
+    #[test]
+    fn test_search_snippets_ranked() -> Result<()> {
+        let mut temp_file = NamedTempFile::new()?;
+        write!(
+            temp_file,
+            r#"{{
+  "takes": {{ "prefix": "tks", "body": "" }},
+  "test_key": {{ "prefix": "tk", "body": "" }},
+  "other": {{ "prefix": "o", "body": "" }}
+}}"#
+        )?;
+        let sources = &[SnippetSource::user(temp_file.path().to_str().unwrap())];
+
+        // Word boundaries rank higher than earlier chars, non-matches are left out
+        let result = search_snippets(
+            sources,
+            Some(Identifier::Key),
+            "tk".to_string(),
+            None,
+            OutputFormat::Plain,
+            false,
+        )?;
+        assert_eq!(result, "test_key\tuser\ntakes\tuser\n");
+
+        let result = search_snippets(
+            sources,
+            Some(Identifier::Prefix),
+            "tk".to_string(),
+            None,
+            OutputFormat::Plain,
+            true,
+        )?;
+        assert_eq!(
+            result,
+            "\x1b[1;33mtk\x1b[0m\tuser\n\x1b[1;33mtk\x1b[0ms\tuser\n"
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,90 @@
+use crate::helpers::render_json::render_json;
+use crate::models::history_record_model::HistoryRecord;
+use crate::models::journal_model::Journal;
+use crate::models::output_format_model::OutputFormat;
+use chrono::Local;
+use prettytable::{row, Table};
+use std::path::Path;
+
+/// Renders the most recent journal entries, newest first.
+pub fn show_history(
+    journal_path: &Path,
+    limit: usize,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let journal = Journal::load(journal_path)?;
+    let records: Vec<HistoryRecord> = journal
+        .entries
+        .iter()
+        .enumerate()
+        .rev()
+        .take(limit)
+        .map(|(index, entry)| HistoryRecord::new(entry, index < journal.applied))
+        .collect();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => return render_json(&records, format),
+        _ if records.is_empty() => return Ok("No changes recorded yet".to_string()),
+        OutputFormat::Plain => {
+            return Ok(records
+                .iter()
+                .map(|record| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}\n",
+                        record.id,
+                        record.timestamp.to_rfc3339(),
+                        record.operation.as_str(),
+                        record.key,
+                        record.path,
+                        record.state
+                    )
+                })
+                .collect());
+        }
+        OutputFormat::Table => {}
+    }
+
+    let mut table = Table::new();
+    table.add_row(row!["#", "When", "Operation", "Key", "Collection", "State"]);
+    for record in records {
+        table.add_row(row![
+            record.id,
+            record
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            record.operation.as_str(),
+            record.key,
+            record.path,
+            record.state
+        ]);
+    }
+    Ok(table.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::snippet_change_model::{Operation, SnippetChange};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_show_history() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let journal_path = temp_dir.path().join("journal.json");
+        assert_eq!(
+            show_history(&journal_path, 10, OutputFormat::Table)?,
+            "No changes recorded yet"
+        );
+        assert_eq!(show_history(&journal_path, 10, OutputFormat::Json)?, "[]");
+
+        let change = SnippetChange::new("rust.json", Operation::Remove, None, None, "", "");
+        Journal::record(&journal_path, change)?;
+
+        let output = show_history(&journal_path, 10, OutputFormat::Table)?;
+        assert!(output.contains("remove"));
+        assert!(output.contains("applied"));
+
+        Ok(())
+    }
+}
@@ -1,27 +1,40 @@
-use crate::models::snippet_model::Snippet;
-use anyhow::Context;
+use crate::helpers::render_json::render_json;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::output_format_model::OutputFormat;
+use crate::models::snippet_record_model::SnippetRecord;
+use crate::models::snippet_source_model::SnippetSource;
 use prettytable::{row, Table};
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
 
-pub fn show_snippet(file_path: &str, key: String) -> anyhow::Result<String> {
-    let path = Path::new(file_path);
+/// Renders the visible snippet for `key` with the layer and file it comes from. Plain output
+/// is the body as the editor inserts it.
+pub fn show_snippet(
+    sources: &[SnippetSource],
+    key: String,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let snippets = LayeredSnippets::load(sources)?;
 
-    let snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
-        let file_contents =
-            fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
-    } else {
-        return Err(anyhow::anyhow!("Snippet file not found or is empty"));
-    };
+    if let Some((snippet, source)) = snippets.get(&key) {
+        match format {
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                return render_json(&SnippetRecord::new(&key, snippet, source), format);
+            }
+            OutputFormat::Plain => return Ok(snippet.body_text()),
+            OutputFormat::Table => {}
+        }
 
-    if let Some(snippet) = snippets.get(&key) {
         let mut table = Table::new();
         table.add_row(row!["Key", key]);
-        table.add_row(row!["Prefix", snippet.prefix]);
-        table.add_row(row!["Description", snippet.description]);
-        table.add_row(row!["Body", snippet.body.join("\n")]);
+        table.add_row(row!["Prefix", snippet.prefix.join(", ")]);
+        table.add_row(row!["Description", snippet.description()]);
+        if let Some(scope) = &snippet.scope {
+            table.add_row(row!["Scope", scope]);
+        }
+        table.add_row(row!["Body", snippet.body_text()]);
+        table.add_row(row![
+            "Source",
+            format!("{} ({})", source.layer, source.path)
+        ]);
 
         Ok(table.to_string())
     } else {
@@ -32,7 +45,9 @@ pub fn show_snippet(file_path: &str, key: String) -> anyhow::Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::snippet_model::Snippet;
     use anyhow::Result;
+    use std::collections::HashMap;
     use std::io::{Seek, SeekFrom, Write};
     use tempfile::NamedTempFile;
 
@@ -46,9 +61,10 @@ mod tests {
         test_snippets.insert(
             "test_key".to_string(),
             Snippet {
-                prefix: "test_prefix".to_string(),
-                body: vec!["test_body".to_string()],
-                description: "test_description".to_string(),
+                prefix: "test_prefix".into(),
+                body: vec!["test_body".to_string()].into(),
+                description: Some("test_description".to_string()),
+                ..Default::default()
             },
         );
 
@@ -62,7 +78,11 @@ mod tests {
         let file_path = temp_file.path().to_str().unwrap();
 
         // Call show_snippet function
-        let result = show_snippet(file_path, "test_key".to_string())?;
+        let result = show_snippet(
+            &[SnippetSource::user(file_path)],
+            "test_key".to_string(),
+            OutputFormat::Table,
+        )?;
 
         // Expected output
         let mut expected_table = Table::new();
@@ -70,6 +90,7 @@ mod tests {
         expected_table.add_row(row!["Prefix", "test_prefix"]);
         expected_table.add_row(row!["Description", "test_description"]);
         expected_table.add_row(row!["Body", "test_body"]);
+        expected_table.add_row(row!["Source", format!("user ({})", file_path)]);
         let expected_output = expected_table.to_string();
 
         // Assert
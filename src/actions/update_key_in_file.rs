@@ -1,20 +1,29 @@
-use crate::models::snippet_model::Snippet;
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::models::snippet_change_model::{Operation, SnippetChange, SnippetEntry};
+use crate::models::snippet_document_model::SnippetDocument;
 use anyhow::Context;
-use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-pub fn update_key_in_file(file_path: &str, old_key: String, new_key: String) -> anyhow::Result<()> {
+pub fn update_key_in_file(
+    file_path: &str,
+    old_key: String,
+    new_key: String,
+) -> anyhow::Result<SnippetChange> {
     if old_key == new_key {
         return Err(anyhow::anyhow!("The new key is the same as the old key"));
     }
 
     let path = Path::new(file_path);
 
-    let mut snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
+    // Hold the lock until the updated file is in place so concurrent runs don't drop changes
+    let _lock = FileLock::acquire(path)?;
+
+    let mut snippets = if path.exists() && path.metadata()?.len() > 0 {
         let file_contents =
             fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
+        SnippetDocument::parse(file_contents).context("Failed to parse the snippets file")?
     } else {
         return Err(anyhow::anyhow!("Snippet file not found or is empty"));
     };
@@ -27,22 +36,32 @@ pub fn update_key_in_file(file_path: &str, old_key: String, new_key: String) ->
     }
 
     let snippet = snippets
-        .remove(&old_key)
+        .get(&old_key)
+        .cloned()
         .ok_or_else(|| anyhow::anyhow!("Snippet with key '{}' not found for updating", old_key))?;
 
-    snippets.insert(new_key, snippet);
+    // Rename the key in place so the snippet keeps its position in the file
+    let original_text = snippets.text().to_string();
+    snippets.rename(&old_key, &new_key)?;
 
-    let updated_contents =
-        serde_json::to_string_pretty(&snippets).context("Failed to serialize snippets")?;
-    fs::write(file_path, updated_contents).context("Failed to write to the snippets file")?;
+    atomic_write(path, snippets.text()).context("Failed to write to the snippets file")?;
 
-    Ok(())
+    Ok(SnippetChange::new(
+        file_path,
+        Operation::Rename,
+        Some(SnippetEntry::new(&old_key, &snippet)),
+        Some(SnippetEntry::new(&new_key, &snippet)),
+        &original_text,
+        snippets.text(),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::snippet_model::Snippet;
     use anyhow::Result;
+    use std::collections::HashMap;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -58,9 +77,10 @@ mod tests {
         snippets.insert(
             initial_key.to_string(),
             Snippet {
-                prefix: "test_prefix".to_string(),
-                body: vec!["test_body".to_string()],
-                description: "test_description".to_string(),
+                prefix: "test_prefix".into(),
+                body: vec!["test_body".to_string()].into(),
+                description: Some("test_description".to_string()),
+                ..Default::default()
             },
         );
         let contents = serde_json::to_string(&snippets)?;
@@ -79,4 +99,24 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_update_key_keeps_unknown_fields() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let file_path = temp_file.path().to_str().unwrap();
+
+        fs::write(
+            file_path,
+            r#"{ "old_key": { "prefix": "p", "body": ["b"], "isFileTemplate": true, "team": { "owner": "core" } } }"#,
+        )?;
+
+        update_key_in_file(file_path, "old_key".to_string(), "new_key".to_string())?;
+
+        let updated_contents = fs::read_to_string(file_path)?;
+        let updated: HashMap<String, serde_json::Value> = serde_json::from_str(&updated_contents)?;
+        assert_eq!(updated["new_key"]["isFileTemplate"], true);
+        assert_eq!(updated["new_key"]["team"]["owner"], "core");
+
+        Ok(())
+    }
 }
@@ -1,24 +1,35 @@
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::models::snippet_change_model::{Operation, SnippetChange, SnippetEntry};
+use crate::models::snippet_document_model::SnippetDocument;
 use crate::models::snippet_model::Snippet;
+use crate::models::string_or_vec_model::StringOrVec;
 use anyhow::Context;
-use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 pub fn write_snippet_to_file(
     file_path: &str,
     key: String,
-    prefix: String,
+    prefix: Vec<String>,
     description: String,
+    scope: Option<String>,
     body: Vec<String>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<SnippetChange> {
     let path = Path::new(file_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create directory")?;
+    }
+
+    // Hold the lock until the updated file is in place so concurrent runs don't drop changes
+    let _lock = FileLock::acquire(path)?;
 
-    let mut snippets: HashMap<String, Snippet> = if path.exists() && path.metadata()?.len() > 0 {
+    let mut snippets = if path.exists() && path.metadata()?.len() > 0 {
         let file_contents =
             fs::read_to_string(file_path).context("Failed to read the snippets file")?;
-        serde_json::from_str(&file_contents).context("Failed to parse the snippets file")?
+        SnippetDocument::parse(file_contents).context("Failed to parse the snippets file")?
     } else {
-        HashMap::new()
+        SnippetDocument::default()
     };
 
     // Check for duplicate key or prefix
@@ -28,7 +39,10 @@ pub fn write_snippet_to_file(
             key
         ));
     }
-    if snippets.values().any(|s| s.prefix == prefix) {
+    if let Some(prefix) = prefix
+        .iter()
+        .find(|p| snippets.values().any(|s| s.prefix.contains(p)))
+    {
         return Err(anyhow::anyhow!(
             "A snippet with prefix '{}' already exists",
             prefix
@@ -37,18 +51,26 @@ pub fn write_snippet_to_file(
 
     // Add the new snippet
     let new_snippet = Snippet {
-        prefix,
-        body,
-        description,
+        prefix: StringOrVec::from_values(prefix),
+        body: StringOrVec::Multiple(body),
+        description: Some(description),
+        scope: scope.filter(|scope| !scope.trim().is_empty()),
+        ..Default::default()
     };
-    snippets.insert(key, new_snippet);
+    let original_text = snippets.text().to_string();
+    snippets.insert(&key, &new_snippet)?;
 
-    // Write the updated HashMap back to the JSON file
-    let updated_contents =
-        serde_json::to_string_pretty(&snippets).context("Failed to serialize snippets")?;
-    fs::write(file_path, updated_contents).context("Failed to write to the snippets file")?;
+    // Write the updated document back to the JSON file
+    atomic_write(path, snippets.text()).context("Failed to write to the snippets file")?;
 
-    Ok(())
+    Ok(SnippetChange::new(
+        file_path,
+        Operation::Add,
+        None,
+        Some(SnippetEntry::new(&key, &new_snippet)),
+        &original_text,
+        snippets.text(),
+    ))
 }
 
 #[cfg(test)]
@@ -66,7 +88,7 @@ mod tests {
         let test_path = temp_file.path();
 
         let test_key = "test_key".to_string();
-        let test_prefix = "test_prefix".to_string();
+        let test_prefix = vec!["test_prefix".to_string()];
         let test_description = "test_description".to_string();
         let test_body = vec!["test_body".to_string()];
 
@@ -76,6 +98,7 @@ mod tests {
             test_key.clone(),
             test_prefix.clone(),
             test_description.clone(),
+            None,
             test_body.clone(),
         )
         .expect("Failed to write test snippet to file");
@@ -88,9 +111,9 @@ mod tests {
         // Check if the test data is correctly written
         assert!(snippets.contains_key(&test_key));
         let snippet = snippets.get(&test_key).unwrap();
-        assert_eq!(snippet.prefix, test_prefix);
-        assert_eq!(snippet.description, test_description);
-        assert_eq!(snippet.body, test_body);
+        assert_eq!(snippet.prefix.to_vec(), test_prefix);
+        assert_eq!(snippet.description(), test_description);
+        assert_eq!(snippet.body.to_vec(), test_body);
 
         // No need for explicit cleanup, as NamedTempFile automatically deletes the file
     }
@@ -109,9 +132,10 @@ mod tests {
         initial_snippets.insert(
             test_key.clone(),
             Snippet {
-                prefix: test_prefix.clone(),
-                description: test_description.clone(),
-                body: test_body.clone(),
+                prefix: test_prefix.clone().into(),
+                description: Some(test_description.clone()),
+                body: test_body.clone().into(),
+                ..Default::default()
             },
         );
         let initial_contents = serde_json::to_string_pretty(&initial_snippets).unwrap();
@@ -121,8 +145,9 @@ mod tests {
         let result = write_snippet_to_file(
             test_path.to_str().unwrap(),
             test_key,
-            "another_prefix".to_string(),
+            vec!["another_prefix".to_string()],
             "another_description".to_string(),
+            None,
             vec!["another_body".to_string()],
         );
         assert!(result.is_err());
@@ -142,9 +167,10 @@ mod tests {
         initial_snippets.insert(
             "test_key_1".to_string(),
             Snippet {
-                prefix: test_prefix.clone(),
-                description: "description_1".to_string(),
-                body: vec!["body_1".to_string()],
+                prefix: test_prefix.clone().into(),
+                description: Some("description_1".to_string()),
+                body: vec!["body_1".to_string()].into(),
+                ..Default::default()
             },
         );
         let initial_contents = serde_json::to_string_pretty(&initial_snippets).unwrap();
@@ -154,12 +180,93 @@ mod tests {
         let result = write_snippet_to_file(
             test_path.to_str().unwrap(),
             "test_key_2".to_string(),
-            test_prefix,
+            vec![test_prefix],
             "new_description".to_string(),
+            None,
             vec!["new_body".to_string()],
         );
         assert!(result.is_err());
 
         // No need for explicit cleanup, as NamedTempFile automatically deletes the file
     }
+
+    #[test]
+    fn test_write_with_prefix_used_by_multi_prefix_snippet() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let test_path = temp_file.path().to_owned();
+
+        // Setup: Write an initial snippet with several prefixes
+        write!(
+            temp_file,
+            r#"{{ "impl_deref": {{ "prefix": ["impl_deref", "deref"], "body": "impl Deref" }} }}"#
+        )
+        .unwrap();
+
+        // Attempt to write another snippet using one of those prefixes
+        let result = write_snippet_to_file(
+            test_path.to_str().unwrap(),
+            "deref_mut".to_string(),
+            vec!["deref_mut".to_string(), "deref".to_string()],
+            "new_description".to_string(),
+            None,
+            vec!["new_body".to_string()],
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "A snippet with prefix 'deref' already exists"
+        );
+    }
+
+    #[test]
+    fn test_write_keeps_comments_and_order() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let test_path = temp_file.path().to_str().unwrap();
+
+        let initial_contents = "{\n  // team snippets\n  \"b\": { \"prefix\": \"b\", \"body\": [\"b\"] },\n  \"a\": { \"prefix\": \"a\", \"body\": [\"a\"] },\n}\n";
+        fs::write(test_path, initial_contents).unwrap();
+
+        write_snippet_to_file(
+            test_path,
+            "c".to_string(),
+            vec!["c".to_string()],
+            "c".to_string(),
+            None,
+            vec!["c".to_string()],
+        )
+        .unwrap();
+
+        let file_contents = fs::read_to_string(test_path).unwrap();
+        assert!(file_contents.starts_with(&initial_contents[..initial_contents.len() - 2]));
+        assert!(file_contents.contains("// team snippets"));
+        assert!(file_contents.find("\"a\"").unwrap() < file_contents.find("\"c\"").unwrap());
+    }
+
+    #[test]
+    fn test_concurrent_writes_keep_every_snippet() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let test_path = temp_dir.path().join("snippets.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let test_path = test_path.clone();
+                std::thread::spawn(move || {
+                    write_snippet_to_file(
+                        test_path.to_str().unwrap(),
+                        format!("key_{}", i),
+                        vec![format!("prefix_{}", i)],
+                        "description".to_string(),
+                        None,
+                        vec!["body".to_string()],
+                    )
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        let file_contents = fs::read_to_string(&test_path).unwrap();
+        let snippets: HashMap<String, Snippet> = serde_json::from_str(&file_contents).unwrap();
+        assert_eq!(snippets.len(), 8);
+    }
 }
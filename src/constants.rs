@@ -1,2 +1,6 @@
 pub const DEFAULT_SNIPPET_PATH: &str = "~/.config/nvim/snippets/rust/rust.json";
 pub const DEFAULT_CONFIG_PATH: &str = "~/.config/snip/config.json";
+pub const LOCK_TIMEOUT_SECS: u64 = 10;
+pub const JOURNAL_FILE_NAME: &str = "journal.json";
+pub const MAX_JOURNAL_ENTRIES: usize = 200;
+pub const LOG_ENV_VAR: &str = "SNIP_LOG";
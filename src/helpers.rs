@@ -1,3 +1,25 @@
+pub mod atomic_write;
+pub mod copy_to_clipboard;
+pub mod draw_tui;
+pub mod edit_in_editor;
+pub mod edit_snippet_template;
+pub mod escape_snippet_text;
 pub mod expand_home_dir;
+pub mod file_lock;
+pub mod fix_snippet;
+pub mod format_snippet_document;
+pub mod fuzzy_match;
 pub mod get_app_config;
+pub mod hash_contents;
+pub mod highlight_matches;
+pub mod init_logger;
 pub mod is_fuzzy_match;
+pub mod jsonc;
+pub mod lint_snippet_document;
+pub mod prompt_tabstops;
+pub mod read_body;
+pub mod render_json;
+pub mod render_lint_findings;
+pub mod render_snippet_table;
+pub mod resolve_variable;
+pub mod tokenize_code;
@@ -0,0 +1,107 @@
+use anyhow::Context;
+use std::fs;
+#[cfg(unix)]
+use std::fs::Permissions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::Builder;
+
+/// Replaces the file at `path` with `contents` so readers see either the old or the new file,
+/// never a partially written one. A symlink is kept, the file it points to is replaced.
+pub fn atomic_write(path: &Path, contents: &str) -> anyhow::Result<()> {
+    let resolved = fs::canonicalize(path).ok();
+    let path = resolved.as_deref().unwrap_or(path);
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    // The temp file must live in the same directory for the rename to be atomic. It is
+    // created with the default mode left by the umask, instead of tempfile's private 0600.
+    let mut builder = Builder::new();
+    #[cfg(unix)]
+    builder.permissions(Permissions::from_mode(0o666));
+    let mut temp_file = builder
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create a temporary file in {}", dir.display()))?;
+    temp_file
+        .write_all(contents.as_bytes())
+        .context("Failed to write the temporary file")?;
+    temp_file
+        .as_file()
+        .sync_all()
+        .context("Failed to flush the temporary file")?;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp_file.path(), metadata.permissions())
+            .context("Failed to copy the file permissions")?;
+    }
+
+    temp_file
+        .persist(path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_atomic_write_replaces_file() {
+        let temp_dir = tempdir().unwrap();
+        let test_file = temp_dir.path().join("snippets.json");
+        fs::write(&test_file, "old contents that are longer").unwrap();
+
+        atomic_write(&test_file, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "new");
+        // Only the target file is left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_write_permissions() {
+        let temp_dir = tempdir().unwrap();
+        let existing = temp_dir.path().join("snippets.json");
+        fs::write(&existing, "old").unwrap();
+        fs::set_permissions(&existing, Permissions::from_mode(0o640)).unwrap();
+        atomic_write(&existing, "new").unwrap();
+        let mode = fs::metadata(&existing).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+
+        // A new file gets the same mode as one created with `fs::write`
+        let plain = temp_dir.path().join("plain.json");
+        fs::write(&plain, "").unwrap();
+        let created = temp_dir.path().join("journal.json");
+        atomic_write(&created, "new").unwrap();
+        assert_eq!(
+            fs::metadata(&created).unwrap().permissions().mode(),
+            fs::metadata(&plain).unwrap().permissions().mode()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_write_through_symlink() {
+        let temp_dir = tempdir().unwrap();
+        let dotfiles = temp_dir.path().join("dotfiles");
+        fs::create_dir(&dotfiles).unwrap();
+        let target = dotfiles.join("rust.json");
+        fs::write(&target, "old").unwrap();
+        let link = temp_dir.path().join("rust.json");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        atomic_write(&link, "new").unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Context;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard commands tried in order, the first one that runs is used.
+const CLIPBOARD_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Copies text to the system clipboard with the platform's clipboard command.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    for (program, args) in CLIPBOARD_COMMANDS {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        child
+            .stdin
+            .take()
+            .context("Failed to open the clipboard command's input")?
+            .write_all(text.as_bytes())
+            .with_context(|| format!("Failed to write to {}", program))?;
+        let status = child
+            .wait()
+            .with_context(|| format!("Failed to run {}", program))?;
+        if status.success() {
+            log::info!("Copied to the clipboard with {}", program);
+            return Ok(());
+        }
+        log::debug!("{} exited with {}", program, status);
+    }
+    Err(anyhow::anyhow!(
+        "No clipboard command found, install one of: {}",
+        CLIPBOARD_COMMANDS
+            .iter()
+            .map(|(program, _)| *program)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
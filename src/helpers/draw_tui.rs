@@ -0,0 +1,196 @@
+use crate::helpers::fuzzy_match::fuzzy_match;
+use crate::models::tui_state_model::{TuiMode, TuiState};
+use crate::parser::print_snippet_ast::print_snippet_ast;
+use crate::parser::snippet_ast::{Node, SnippetAst};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+const HELP: &str =
+    "Enter copy  ^E edit  ^R rename  ^D duplicate  ^X delete  Tab collection  Esc quit";
+
+/// Draws the query line, the matching snippets next to a preview of the selected one, and
+/// a status line.
+pub fn draw_tui(frame: &mut Frame, state: &TuiState) {
+    let [input_area, main_area, status_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list_area, preview_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .areas(main_area);
+
+    let selected_key = state.selected_entry().map(|entry| entry.key.as_str());
+    let input = match (&state.mode, selected_key) {
+        (TuiMode::Rename(input), Some(key)) => format!("Rename '{}' to: {}", key, input),
+        (TuiMode::Duplicate(input), Some(key)) => format!("Duplicate '{}' as: {}", key, input),
+        (TuiMode::ConfirmDelete, Some(key)) => format!("Delete '{}'? (y/n)", key),
+        _ => format!("> {}", state.query),
+    };
+    frame.render_widget(Paragraph::new(input.as_str()), input_area);
+    if !matches!(state.mode, TuiMode::ConfirmDelete) {
+        let x = input_area.x + (input.chars().count() as u16).min(input_area.width);
+        frame.set_cursor_position((x, input_area.y));
+    }
+
+    let collection = state.collection();
+    let items: Vec<ListItem> = state
+        .matches
+        .iter()
+        .map(|&index| {
+            let entry = &state.entries[index];
+            let prefixes = entry.snippet.prefixes().collect::<Vec<_>>().join(", ");
+            let positions = fuzzy_match(&entry.key, &state.query)
+                .map(|found| found.positions)
+                .unwrap_or_default();
+            let mut spans = highlight_key(&entry.key, &positions);
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(prefixes, Style::default().fg(Color::DarkGray)));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let title = format!(
+        " {} ({}/{}) ",
+        collection.name,
+        state.matches.len(),
+        state.entries.len()
+    );
+    let list = List::new(items)
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    let mut list_state = ListState::default().with_selected(selected_key.map(|_| state.selected));
+    frame.render_stateful_widget(list, list_area, &mut list_state);
+
+    let preview = match state.selected_entry() {
+        Some(entry) => {
+            let snippet = &entry.snippet;
+            let field = |name: &str, value: String| {
+                Line::from(vec![
+                    Span::raw(format!("{}: ", name)).bold(),
+                    Span::raw(value),
+                ])
+            };
+            let mut lines = vec![
+                field("Prefix", snippet.prefixes().collect::<Vec<_>>().join(", ")),
+                field("Description", snippet.description().to_string()),
+                field("Scope", snippet.scopes().collect::<Vec<_>>().join(", ")),
+                field(
+                    "Source",
+                    format!("{} ({})", entry.source.layer, entry.source.path),
+                ),
+                Line::default(),
+            ];
+            lines.extend(highlight_body(&snippet.body_ast()));
+            Paragraph::new(lines).block(Block::bordered().title(format!(" {} ", entry.key)))
+        }
+        None => Paragraph::new("No matching snippets").block(Block::bordered()),
+    };
+    frame.render_widget(preview, preview_area);
+
+    let status = state.status.as_deref().unwrap_or(HELP);
+    frame.render_widget(Paragraph::new(status).fg(Color::DarkGray), status_area);
+}
+
+/// The key with the chars matched by the query in bold yellow.
+fn highlight_key(key: &str, positions: &[usize]) -> Vec<Span<'static>> {
+    let matched = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    key.chars()
+        .enumerate()
+        .map(|(index, c)| match positions.contains(&index) {
+            true => Span::styled(c.to_string(), matched),
+            false => Span::raw(c.to_string()),
+        })
+        .collect()
+}
+
+/// The body as lines, with tabstops, choices and variables colored.
+fn highlight_body(ast: &SnippetAst) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default()];
+    for node in &ast.nodes {
+        let style = match node {
+            Node::Text { .. } => Style::default(),
+            Node::Tabstop { .. } | Node::Placeholder { .. } => Style::default().fg(Color::Yellow),
+            Node::Choice { .. } => Style::default().fg(Color::Magenta),
+            Node::Variable { .. } => Style::default().fg(Color::Cyan),
+        };
+        let text = print_snippet_ast(&SnippetAst {
+            nodes: vec![node.clone()],
+        });
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(Line::default());
+            }
+            if !part.is_empty() {
+                lines
+                    .last_mut()
+                    .unwrap()
+                    .push_span(Span::styled(part.to_string(), style));
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::collection_model::Collection;
+    use crate::models::snippet_source_model::SnippetSource;
+    use crate::models::tui_state_model::TuiCollection;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_draw_tui() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("rust.json");
+        fs::write(
+            &path,
+            r#"{ "test": { "prefix": "tst", "body": ["fn ${1:name}() {", "\t$0", "}"], "description": "Test" } }"#,
+        )?;
+        let collection = Collection::from_path(path.to_str().unwrap().to_string());
+        let state = TuiState::new(
+            vec![TuiCollection {
+                name: "rust".to_string(),
+                sources: vec![SnippetSource::user(&collection.path)],
+                collection,
+            }],
+            0,
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12))?;
+        terminal.draw(|frame| draw_tui(frame, &state))?;
+        let buffer = terminal.backend().buffer();
+        let screen: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+
+        assert!(screen[0].starts_with("> "));
+        assert!(screen[1].contains(" rust (1/1) "));
+        assert!(screen[2].contains("> test tst"));
+        assert!(screen.iter().any(|line| line.contains("fn ${1:name}() {")));
+        // Tabstops stand out from the text around them
+        let y = screen
+            .iter()
+            .position(|line| line.contains("fn ${1"))
+            .unwrap();
+        let byte = screen[y].find("${1").unwrap();
+        let x = screen[y][..byte].chars().count() as u16;
+        assert_eq!(buffer[(x, y as u16)].fg, Color::Yellow);
+        assert!(screen[11].starts_with("Enter copy"));
+        Ok(())
+    }
+}
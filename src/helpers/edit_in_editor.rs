@@ -0,0 +1,69 @@
+use anyhow::Context;
+use std::fs;
+use std::process::Command;
+use tempfile::Builder;
+
+/// Opens `text` in `editor` as a temp file with the given extension, and parses the text it
+/// holds once the editor exits.
+///
+/// `editor` may hold arguments, e.g. `code --wait`. When parsing fails the file is kept and
+/// its path added to the error, so the changes aren't lost.
+pub fn edit_in_editor<T>(
+    editor: &str,
+    text: &str,
+    extension: &str,
+    parse: impl FnOnce(&str) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let file = Builder::new()
+        .prefix("snip-")
+        .suffix(&format!(".{}", extension))
+        .tempfile()
+        .context("Failed to create a temporary file")?;
+    fs::write(file.path(), text).context("Failed to write the temporary file")?;
+
+    let mut words = editor.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The editor command is empty"))?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(file.path())
+        .status()
+        .with_context(|| format!("Failed to run the editor {}", editor))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "The editor {} exited with {}",
+            editor,
+            status
+        ));
+    }
+
+    let edited = fs::read_to_string(file.path()).context("Failed to read the edited file")?;
+    parse(&edited).or_else(|error| {
+        let (_, path) = file.keep().context("Failed to keep the edited file")?;
+        Err(error.context(format!("Your changes are kept in {}", path.display())))
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_in_editor() -> anyhow::Result<()> {
+        let edited = edit_in_editor("sed -i s/old/new/", "old text\n", "rs", |text| {
+            Ok(text.to_string())
+        })?;
+        assert_eq!(edited, "new text\n");
+
+        let error = edit_in_editor("true", "text", "rs", |_| -> anyhow::Result<()> {
+            Err(anyhow::anyhow!("invalid"))
+        })
+        .unwrap_err();
+        let kept = format!("{}", error);
+        let path = kept.trim_start_matches("Your changes are kept in ");
+        assert_eq!(fs::read_to_string(path)?, "text");
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
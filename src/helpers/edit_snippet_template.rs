@@ -0,0 +1,33 @@
+use crate::helpers::edit_in_editor::edit_in_editor;
+use crate::helpers::resolve_variable::resolve_variable;
+use crate::models::collection_model::Collection;
+use crate::models::expand_context_model::ExpandContext;
+use crate::models::snippet_template_model::SnippetTemplate;
+use std::env;
+
+/// Opens a snippet template in `$VISUAL`, `$EDITOR` or `vi`, as a file of the collection's
+/// language so it gets highlighted, and returns the template as edited.
+pub fn edit_snippet_template(
+    template: &SnippetTemplate,
+    collection: &Collection,
+) -> anyhow::Result<SnippetTemplate> {
+    let context = ExpandContext {
+        lang: Some(collection.lang.clone()),
+        ..Default::default()
+    };
+    // Languages without line comments, e.g. HTML, get `#` headers
+    let comment = resolve_variable("LINE_COMMENT", &context)
+        .filter(|comment| !comment.is_empty())
+        .unwrap_or_else(|| "#".to_string());
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    log::debug!("Editing the snippet with {}", editor);
+
+    edit_in_editor(
+        &editor,
+        &template.render(&comment),
+        collection.extension(),
+        |text| SnippetTemplate::parse(text, &comment),
+    )
+}
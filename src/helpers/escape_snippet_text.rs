@@ -0,0 +1,37 @@
+/// Escapes text so a snippet inserts it literally: `$` and `}` get a backslash, and so does a
+/// backslash that would otherwise escape one of them.
+pub fn escape_snippet_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' | '}' => escaped.push('\\'),
+            '\\' if matches!(chars.peek(), Some('$' | '}' | '\\')) => escaped.push('\\'),
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_snippet_body::parse_snippet_body;
+    use crate::parser::snippet_ast::Node;
+
+    #[test]
+    fn test_escaped_text_parses_back_to_itself() {
+        let code = r#"println!("${x} costs $5 \$ {}\n", "\\");"#;
+        let escaped = escape_snippet_text(code);
+
+        assert_eq!(
+            escaped,
+            r#"println!("\${x\} costs \$5 \\\$ {\}\n", "\\\");"#
+        );
+        match parse_snippet_body(&escaped).nodes.as_slice() {
+            [Node::Text { text, .. }] => assert_eq!(text.value, code),
+            nodes => panic!("expected a single text node, got {:?}", nodes),
+        }
+    }
+}
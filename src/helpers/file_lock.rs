@@ -0,0 +1,132 @@
+use crate::constants::LOCK_TIMEOUT_SECS;
+use anyhow::Context;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// An advisory lock guarding a file for a whole read-modify-write. Released on drop.
+///
+/// The lock is taken on a `.<name>.lock` file next to the guarded file, since the guarded file
+/// itself is replaced by a rename when it is written. The lock file is removed on drop, so
+/// only a held lock leaves one behind.
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    pub fn acquire(path: &Path) -> anyhow::Result<FileLock> {
+        FileLock::acquire_with_timeout(path, Duration::from_secs(LOCK_TIMEOUT_SECS))
+    }
+
+    pub fn acquire_with_timeout(path: &Path, timeout: Duration) -> anyhow::Result<FileLock> {
+        let lock_path = lock_path(path);
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent).context("Failed to create directory")?;
+        }
+
+        let started = Instant::now();
+        let mut logged = false;
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&lock_path)
+                .with_context(|| format!("Failed to open lock file {}", lock_path.display()))?;
+            match file.try_lock() {
+                // The previous holder removed the file we opened, lock the one there now
+                Ok(()) if !is_current(&file, &lock_path) => continue,
+                Ok(()) => {
+                    return Ok(FileLock {
+                        _file: file,
+                        path: lock_path,
+                    })
+                }
+                Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                    if !logged {
+                        log::debug!("Waiting for the lock on {}", path.display());
+                        logged = true;
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(anyhow::anyhow!(
+                        "Timed out after {:?} waiting for the lock on {}, another snip process is still using it",
+                        timeout,
+                        path.display()
+                    ));
+                }
+                Err(TryLockError::Error(err)) => {
+                    return Err(err)
+                        .with_context(|| format!("Failed to lock {}", lock_path.display()));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Removed while still held, so a process waiting on it notices and opens a new one
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::debug!("Failed to remove {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+/// Whether `file` is still the file at `path`.
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(opened), Ok(current)) => opened.dev() == current.dev() && opened.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_current(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.lock", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_times_out_while_held() {
+        let temp_dir = tempdir().unwrap();
+        let test_file = temp_dir.path().join("snippets.json");
+
+        let lock = FileLock::acquire(&test_file).unwrap();
+        let result = FileLock::acquire_with_timeout(&test_file, Duration::from_millis(100));
+        assert!(result.unwrap_err().to_string().starts_with("Timed out"));
+
+        drop(lock);
+        assert!(FileLock::acquire_with_timeout(&test_file, Duration::from_millis(100)).is_ok());
+    }
+
+    #[test]
+    fn test_lock_file_removed_on_drop() {
+        let temp_dir = tempdir().unwrap();
+        let test_file = temp_dir.path().join("lib.rs");
+
+        let lock = FileLock::acquire(&test_file).unwrap();
+        assert!(temp_dir.path().join(".lib.rs.lock").exists());
+        drop(lock);
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+}
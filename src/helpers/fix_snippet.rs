@@ -0,0 +1,111 @@
+use crate::helpers::lint_snippet_document::unescaped;
+use crate::models::lint_rule_model::LintRule;
+use crate::models::snippet_model::Snippet;
+use crate::parser::print_snippet_ast::print_snippet_ast;
+use crate::parser::snippet_ast::Node;
+use std::collections::HashMap;
+
+/// Fixes the problems of the given rules that can be fixed without changing what the
+/// snippet produces: trailing whitespace, unescaped `$`/`}` and gaps in tabstop numbering.
+pub fn fix_snippet(snippet: &Snippet, rules: &[LintRule]) -> Snippet {
+    let mut fixed = snippet.clone();
+
+    if rules.contains(&LintRule::TrailingWhitespace) {
+        let lines = fixed
+            .body
+            .iter()
+            .map(|line| {
+                line.split('\n')
+                    .map(str::trim_end)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect();
+        fixed.body.replace(lines);
+    }
+
+    let escape = rules.contains(&LintRule::UnescapedCharacter);
+    let renumber = rules.contains(&LintRule::TabstopGap);
+    if escape || renumber {
+        let mut ast = fixed.body_ast();
+        let indices: HashMap<u32, u32> = ast
+            .tabstops()
+            .into_iter()
+            .filter(|&index| index != 0)
+            .zip(1..)
+            .filter(|_| renumber)
+            .collect();
+        fix_nodes(&mut ast.nodes, escape, &indices);
+        let body = print_snippet_ast(&ast);
+        if body != fixed.body_text() {
+            fixed
+                .body
+                .replace(body.split('\n').map(str::to_string).collect());
+        }
+    }
+    fixed
+}
+
+fn fix_nodes(nodes: &mut [Node], escape: bool, indices: &HashMap<u32, u32>) {
+    for node in nodes {
+        match node {
+            Node::Text { text, .. } if escape => {
+                for offset in unescaped(&text.raw).into_iter().rev() {
+                    text.raw.insert(offset, '\\');
+                }
+            }
+            Node::Tabstop { index, .. } | Node::Choice { index, .. } => {
+                *index = indices.get(index).copied().unwrap_or(*index);
+            }
+            Node::Placeholder {
+                index, children, ..
+            } => {
+                *index = indices.get(index).copied().unwrap_or(*index);
+                fix_nodes(children, escape, indices);
+            }
+            Node::Variable {
+                default: Some(children),
+                ..
+            } => fix_nodes(children, escape, indices),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_snippet() {
+        let snippet = Snippet {
+            prefix: "fn".into(),
+            body: vec![
+                "fn ${2:name}() -> $5 { \t".to_string(),
+                "\t${2}; // costs $ 5 }".to_string(),
+                "$0}".to_string(),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let fixed = fix_snippet(
+            &snippet,
+            &[
+                LintRule::TrailingWhitespace,
+                LintRule::UnescapedCharacter,
+                LintRule::TabstopGap,
+            ],
+        );
+
+        assert_eq!(
+            fixed.body.to_vec(),
+            vec![
+                "fn ${1:name}() -> $2 {",
+                "\t${1}; // costs \\$ 5 \\}",
+                "$0}"
+            ]
+        );
+        assert_eq!(fix_snippet(&snippet, &[]), snippet);
+    }
+}
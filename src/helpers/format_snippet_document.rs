@@ -0,0 +1,124 @@
+use crate::helpers::jsonc::{self, line_col, Style};
+use crate::models::key_order_model::KeyOrder;
+use crate::models::snippet_document_model::SnippetDocument;
+use crate::models::string_or_vec_model::StringOrVec;
+
+/// Writes a collection in its canonical form: one snippet per member in key order (or file
+/// order), the file's indentation used throughout, and bodies as arrays of lines.
+///
+/// Comments between snippets move with the snippet that follows them. Comments inside a
+/// snippet and duplicated keys would be lost, so they are reported as errors instead.
+pub fn format_snippet_document(
+    document: &SnippetDocument,
+    key_order: KeyOrder,
+) -> anyhow::Result<String> {
+    let text = document.text();
+    let Some(root) = document.root() else {
+        return Ok(text.to_string());
+    };
+    let style = Style::detect(text, Some(root));
+    let members = root.members();
+
+    let mut entries = Vec::new();
+    let mut comments_start = root.span().start + 1;
+    for (index, member) in members.iter().enumerate() {
+        if members[..index].iter().any(|m| m.key == member.key) {
+            let (line, _) = line_col(text, member.key_span.start);
+            return Err(anyhow::anyhow!(
+                "Key '{}' is defined again at line {}, keep one of them first",
+                member.key,
+                line
+            ));
+        }
+        if let Some(comment) = jsonc::comments(text, member.value.span()).first() {
+            let (line, _) = line_col(text, member.value.span().start);
+            return Err(anyhow::anyhow!(
+                "Snippet '{}' at line {} holds the comment '{}', move it above the snippet first",
+                member.key,
+                line,
+                comment
+            ));
+        }
+        let comments = jsonc::comments(text, comments_start..member.key_span.start);
+        comments_start = member.value.span().end;
+
+        let mut snippet = document
+            .get(&member.key)
+            .expect("every member is a snippet")
+            .clone();
+        let lines = snippet
+            .body
+            .iter()
+            .flat_map(|line| line.split('\n'))
+            .map(str::to_string)
+            .collect();
+        snippet.body = StringOrVec::Multiple(lines);
+        entries.push((member.key.as_str(), comments, snippet));
+    }
+    if key_order == KeyOrder::Sorted {
+        entries.sort_by_key(|(key, ..)| *key);
+    }
+    let trailing = jsonc::comments(text, comments_start..root.span().end - 1);
+
+    let indent = &style.indent;
+    let eol = &style.eol;
+    let mut out = format!("{{{}", eol);
+    for (index, (key, comments, snippet)) in entries.iter().enumerate() {
+        for comment in comments {
+            out.push_str(&format!("{}{}{}", indent, comment, eol));
+        }
+        let value = serde_json::to_value(snippet)?;
+        let separator = if index + 1 < entries.len() { "," } else { "" };
+        out.push_str(&format!(
+            "{}{}: {}{}{}",
+            indent,
+            serde_json::Value::String(key.to_string()),
+            style.to_json(&value, indent),
+            separator,
+            eol
+        ));
+    }
+    for comment in trailing {
+        out.push_str(&format!("{}{}{}", indent, comment, eol));
+    }
+    out.push_str(&format!("}}{}", eol));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_snippet_document() {
+        let text = "{\r\n\t// Logging\r\n\t\"log\": {\"body\": \"a\\nb\", \"prefix\": \"log\", \"luasnip\": {\"priority\": 1}},\r\n\t\"dbg\": {\r\n\t\t\"prefix\": [\"dbg\"],\r\n\t\t\"body\": [\"dbg!($1)\"],\r\n\t},\r\n\t// end\r\n}";
+        let document = SnippetDocument::parse(text.to_string()).unwrap();
+
+        let formatted = format_snippet_document(&document, KeyOrder::Sorted).unwrap();
+        assert_eq!(
+            formatted,
+            "{\r\n\t\"dbg\": {\r\n\t\t\"prefix\": [\r\n\t\t\t\"dbg\"\r\n\t\t],\r\n\t\t\"body\": [\r\n\t\t\t\"dbg!($1)\"\r\n\t\t]\r\n\t},\r\n\t// Logging\r\n\t\"log\": {\r\n\t\t\"prefix\": \"log\",\r\n\t\t\"body\": [\r\n\t\t\t\"a\",\r\n\t\t\t\"b\"\r\n\t\t],\r\n\t\t\"luasnip\": {\r\n\t\t\t\"priority\": 1\r\n\t\t}\r\n\t}\r\n\t// end\r\n}\r\n"
+        );
+        // Formatting is stable
+        let document = SnippetDocument::parse(formatted.clone()).unwrap();
+        assert_eq!(
+            format_snippet_document(&document, KeyOrder::Sorted).unwrap(),
+            formatted
+        );
+
+        let document = SnippetDocument::parse(text.to_string()).unwrap();
+        let insertion = format_snippet_document(&document, KeyOrder::Insertion).unwrap();
+        assert!(insertion.starts_with("{\r\n\t// Logging\r\n\t\"log\": {"));
+    }
+
+    #[test]
+    fn test_format_refuses_to_drop_content() {
+        let commented = "{ \"a\": { \"prefix\": \"a\", // why\n \"body\": \"a\" } }";
+        let document = SnippetDocument::parse(commented.to_string()).unwrap();
+        assert!(format_snippet_document(&document, KeyOrder::Sorted).is_err());
+
+        let duplicated = "{ \"a\": { \"prefix\": \"a\", \"body\": \"a\" }, \"a\": { \"prefix\": \"b\", \"body\": \"b\" } }";
+        let document = SnippetDocument::parse(duplicated.to_string()).unwrap();
+        assert!(format_snippet_document(&document, KeyOrder::Sorted).is_err());
+    }
+}
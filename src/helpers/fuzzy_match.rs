@@ -0,0 +1,200 @@
+use crate::models::fuzzy_match_model::FuzzyMatch;
+use std::cmp::Reverse;
+
+// Scores of fzf, so results rank the way its users expect
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+const BONUS_BOUNDARY_WHITE: i32 = BONUS_BOUNDARY + 2;
+const BONUS_BOUNDARY_DELIMITER: i32 = BONUS_BOUNDARY + 1;
+const BONUS_NON_WORD: i32 = SCORE_MATCH / 2;
+const BONUS_CAMEL: i32 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i32 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+const NO_MATCH: i32 = i32::MIN / 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    White,
+    Delimiter,
+    NonWord,
+    Lower,
+    Upper,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    match c {
+        _ if c.is_whitespace() => CharClass::White,
+        '_' | '-' | '/' | '.' | ',' | ':' | ';' | '|' => CharClass::Delimiter,
+        _ if c.is_lowercase() => CharClass::Lower,
+        _ if c.is_uppercase() => CharClass::Upper,
+        _ if c.is_numeric() => CharClass::Number,
+        _ if c.is_alphabetic() => CharClass::Lower,
+        _ => CharClass::NonWord,
+    }
+}
+
+/// Bonus for matching a char of `class` following one of `previous`, rewarding the start
+/// of words, `snake_case` parts and `camelCase` humps.
+fn bonus(previous: CharClass, class: CharClass) -> i32 {
+    use CharClass::*;
+    match (previous, class) {
+        (White, Lower | Upper | Number) => BONUS_BOUNDARY_WHITE,
+        (Delimiter, Lower | Upper | Number) => BONUS_BOUNDARY_DELIMITER,
+        (NonWord, Lower | Upper | Number) => BONUS_BOUNDARY,
+        (Lower, Upper) | (Lower | Upper, Number) => BONUS_CAMEL,
+        (_, Lower | Upper | Number) => 0,
+        (_, White) => BONUS_BOUNDARY_WHITE,
+        (_, Delimiter | NonWord) => BONUS_NON_WORD,
+    }
+}
+
+/// Matches the chars of `pattern` in order anywhere in `text`, picking the positions with
+/// the best score. Matching is smart-case, it ignores case unless `pattern` has an
+/// uppercase char.
+pub fn fuzzy_match(text: &str, pattern: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = pattern.chars().any(char::is_uppercase);
+    let fold = |c: char| match case_sensitive {
+        true => c,
+        false => c.to_lowercase().next().unwrap_or(c),
+    };
+    let pattern: Vec<char> = pattern.chars().map(fold).collect();
+    let chars: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = chars.iter().copied().map(fold).collect();
+
+    // Most texts don't match at all, rule them out before scoring
+    let mut rest = pattern.iter().peekable();
+    for c in &folded {
+        if rest.peek() == Some(&c) {
+            rest.next();
+        }
+    }
+    if rest.peek().is_some() {
+        return None;
+    }
+    if pattern.is_empty() {
+        return Some(FuzzyMatch::default());
+    }
+
+    let mut previous = CharClass::White;
+    let bonuses: Vec<i32> = chars
+        .iter()
+        .map(|&c| {
+            let class = char_class(c);
+            let bonus = bonus(previous, class);
+            previous = class;
+            bonus
+        })
+        .collect();
+
+    // `score[i][j]` is the best score with `pattern[i]` matched at `j`, `from[i][j]` where
+    // `pattern[i - 1]` is matched then and `run_bonus[i][j]` the bonus its run started with
+    let (n, m) = (chars.len(), pattern.len());
+    let mut score = vec![vec![NO_MATCH; n]; m];
+    let mut from = vec![vec![0; n]; m];
+    let mut run_bonus = vec![vec![0; n]; m];
+    for j in 0..n {
+        if folded[j] == pattern[0] {
+            score[0][j] = SCORE_MATCH + bonuses[j] * BONUS_FIRST_CHAR_MULTIPLIER;
+            run_bonus[0][j] = bonuses[j];
+        }
+    }
+    for i in 1..m {
+        // Best match of `pattern[i - 1]` before `j - 1`, less the penalty of the gap to `j`
+        let mut gap: Option<(i32, usize)> = None;
+        for j in i..n {
+            if j >= 2 && score[i - 1][j - 2] > NO_MATCH {
+                let started = score[i - 1][j - 2] + SCORE_GAP_START;
+                gap = match gap {
+                    Some((extended, k)) if extended + SCORE_GAP_EXTENSION > started => {
+                        Some((extended + SCORE_GAP_EXTENSION, k))
+                    }
+                    _ => Some((started, j - 2)),
+                };
+            } else if let Some((extended, k)) = gap {
+                gap = Some((extended + SCORE_GAP_EXTENSION, k));
+            }
+            if folded[j] != pattern[i] {
+                continue;
+            }
+
+            let mut best =
+                gap.map(|(gapped, k)| (gapped + SCORE_MATCH + bonuses[j], k, bonuses[j]));
+            if score[i - 1][j - 1] > NO_MATCH {
+                // A run keeps the bonus of its first char, unless a stronger boundary follows
+                let mut first_bonus = run_bonus[i - 1][j - 1];
+                if bonuses[j] >= BONUS_BOUNDARY && bonuses[j] > first_bonus {
+                    first_bonus = bonuses[j];
+                }
+                let bonus = bonuses[j].max(first_bonus).max(BONUS_CONSECUTIVE);
+                let consecutive = score[i - 1][j - 1] + SCORE_MATCH + bonus;
+                if best.is_none_or(|(gapped, _, _)| consecutive >= gapped) {
+                    best = Some((consecutive, j - 1, first_bonus));
+                }
+            }
+            if let Some((best_score, k, first_bonus)) = best {
+                score[i][j] = best_score;
+                from[i][j] = k;
+                run_bonus[i][j] = first_bonus;
+            }
+        }
+    }
+
+    // The earliest of the best matches
+    let (end, best_score) = (0..n)
+        .map(|j| (j, score[m - 1][j]))
+        .filter(|&(_, score)| score > NO_MATCH)
+        .max_by_key(|&(j, score)| (score, Reverse(j)))?;
+    let mut positions = vec![0; m];
+    let mut j = end;
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+    Some(FuzzyMatch {
+        score: best_score,
+        positions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match_positions() {
+        assert_eq!(fuzzy_match("moon", "bd"), None);
+        assert_eq!(fuzzy_match("moon", "mn").unwrap().positions, vec![0, 3]);
+        // Word starts, `_` parts and camelCase humps are preferred over earlier chars
+        assert_eq!(
+            fuzzy_match("some_model", "sm").unwrap().positions,
+            vec![0, 5]
+        );
+        assert_eq!(
+            fuzzy_match("formatBody", "fb").unwrap().positions,
+            vec![0, 6]
+        );
+        assert_eq!(
+            fuzzy_match("ab abc", "abc").unwrap().positions,
+            vec![3, 4, 5]
+        );
+        assert_eq!(fuzzy_match("anything", ""), Some(FuzzyMatch::default()));
+    }
+
+    #[test]
+    fn test_fuzzy_match_smart_case() {
+        assert!(fuzzy_match("FooBar", "fb").is_some());
+        assert!(fuzzy_match("foobar", "FB").is_none());
+        assert!(fuzzy_match("FooBar", "FB").is_some());
+    }
+
+    #[test]
+    fn test_fuzzy_match_ranking() {
+        let score = |text| fuzzy_match(text, "tk").unwrap().score;
+        assert!(score("test_key") > score("takes"));
+        assert!(score("tk") > score("test_key"));
+        assert!(score("testKey") > score("testkey"));
+    }
+}
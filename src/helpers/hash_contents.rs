@@ -0,0 +1,23 @@
+/// A stable 64-bit FNV-1a hash of file contents, used to notice changes made outside `snip`.
+///
+/// `std`'s hashers are not guaranteed to be stable across releases, so they can't be persisted.
+pub fn hash_contents(contents: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in contents.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_contents() {
+        assert_eq!(hash_contents(""), "cbf29ce484222325");
+        assert_eq!(hash_contents("a"), "af63dc4c8601ec8c");
+        assert_ne!(hash_contents("{}"), hash_contents("{ }"));
+    }
+}
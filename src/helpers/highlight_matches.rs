@@ -0,0 +1,31 @@
+/// Wraps the chars at `positions` in bold yellow ANSI escapes, a run of them at once.
+pub fn highlight_matches(text: &str, positions: &[usize]) -> String {
+    let mut out = String::new();
+    let mut highlighted = false;
+    for (index, c) in text.chars().enumerate() {
+        let matched = positions.contains(&index);
+        if matched != highlighted {
+            out.push_str(if matched { "\x1b[1;33m" } else { "\x1b[0m" });
+            highlighted = matched;
+        }
+        out.push(c);
+    }
+    if highlighted {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_matches() {
+        assert_eq!(
+            highlight_matches("test_key", &[0, 1, 5]),
+            "\x1b[1;33mte\x1b[0mst_\x1b[1;33mk\x1b[0mey"
+        );
+        assert_eq!(highlight_matches("key", &[]), "key");
+    }
+}
@@ -0,0 +1,52 @@
+use crate::constants::LOG_ENV_VAR;
+use log::LevelFilter;
+use std::env;
+
+/// Sends diagnostics to stderr so stdout only carries command results.
+///
+/// `-v` shows info, `-vv` debug and `-vvv` trace messages, `--quiet` only errors. Without
+/// either, the `SNIP_LOG` filter is used (e.g. `SNIP_LOG=debug` or `SNIP_LOG=snip_cli=trace`),
+/// falling back to warnings.
+pub fn init_logger(verbose: u8, quiet: bool) {
+    let mut builder = env_logger::Builder::new();
+    builder.format_timestamp(None).format_target(false);
+    match level_filter(verbose, quiet) {
+        Some(level) => {
+            builder.filter_level(level);
+        }
+        None => match env::var(LOG_ENV_VAR) {
+            Ok(filters) => {
+                builder.parse_filters(&filters);
+            }
+            Err(_) => {
+                builder.filter_level(LevelFilter::Warn);
+            }
+        },
+    }
+    // A logger may already be set, e.g. when called twice from tests
+    let _ = builder.try_init();
+}
+
+fn level_filter(verbose: u8, quiet: bool) -> Option<LevelFilter> {
+    match (quiet, verbose) {
+        (true, _) => Some(LevelFilter::Error),
+        (false, 0) => None,
+        (false, 1) => Some(LevelFilter::Info),
+        (false, 2) => Some(LevelFilter::Debug),
+        (false, _) => Some(LevelFilter::Trace),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_override_env_filter() {
+        assert_eq!(level_filter(0, false), None);
+        assert_eq!(level_filter(1, false), Some(LevelFilter::Info));
+        assert_eq!(level_filter(2, false), Some(LevelFilter::Debug));
+        assert_eq!(level_filter(5, false), Some(LevelFilter::Trace));
+        assert_eq!(level_filter(0, true), Some(LevelFilter::Error));
+    }
+}
@@ -1,13 +1,9 @@
+use crate::helpers::fuzzy_match::fuzzy_match;
+
+/// Whether the chars of `pattern` appear in order in `text`, with the smart-case of
+/// `fuzzy_match`.
 pub fn is_fuzzy_match(text: &str, pattern: &str) -> bool {
-    let mut pattern_chars = pattern.chars().peekable();
-    for ch in text.chars() {
-        if let Some(&next_pattern_char) = pattern_chars.peek() {
-            if ch == next_pattern_char {
-                pattern_chars.next();
-            }
-        }
-    }
-    pattern_chars.peek().is_none()
+    fuzzy_match(text, pattern).is_some()
 }
 
 #[cfg(test)]
@@ -0,0 +1,820 @@
+//! A small JSONC reader (JSON with `//` and `/* */` comments and trailing commas) that keeps
+//! the byte span of every value, plus text edits that only touch the span being changed.
+
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::ops::Range;
+
+pub type Span = Range<usize>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonNode {
+    Object {
+        span: Span,
+        members: Vec<JsonMember>,
+    },
+    Array {
+        span: Span,
+        items: Vec<JsonNode>,
+    },
+    Scalar {
+        span: Span,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonMember {
+    pub key: String,
+    pub key_span: Span,
+    pub value: JsonNode,
+}
+
+impl JsonNode {
+    pub fn span(&self) -> Span {
+        match self {
+            JsonNode::Object { span, .. }
+            | JsonNode::Array { span, .. }
+            | JsonNode::Scalar { span, .. } => span.clone(),
+        }
+    }
+
+    /// Members of an object in file order, including duplicated keys. Empty for other nodes.
+    pub fn members(&self) -> &[JsonMember] {
+        match self {
+            JsonNode::Object { members, .. } => members,
+            _ => &[],
+        }
+    }
+
+    /// The member serde would keep for `key`, i.e. the last one when a key is duplicated.
+    pub fn member(&self, key: &str) -> Option<&JsonMember> {
+        self.members().iter().rev().find(|m| m.key == key)
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            JsonNode::Object { members, .. } => {
+                let mut map = Map::new();
+                for member in members {
+                    map.insert(member.key.clone(), member.value.to_value());
+                }
+                Value::Object(map)
+            }
+            JsonNode::Array { items, .. } => {
+                Value::Array(items.iter().map(JsonNode::to_value).collect())
+            }
+            JsonNode::Scalar { value, .. } => value.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsoncError {
+    pub message: String,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for JsoncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for JsoncError {}
+
+/// Returns the 1-based line and column of a byte offset.
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |i| &before[i + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+pub fn parse(text: &str) -> Result<JsonNode, JsoncError> {
+    let mut parser = Parser { text, pos: 0 };
+    if text.starts_with('\u{feff}') {
+        parser.pos = '\u{feff}'.len_utf8();
+    }
+    parser.skip_trivia()?;
+    let node = parser.parse_value()?;
+    parser.skip_trivia()?;
+    if parser.pos < text.len() {
+        return Err(parser.error("Unexpected trailing characters"));
+    }
+    Ok(node)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> JsoncError {
+        let (line, column) = line_col(self.text, self.pos);
+        JsoncError {
+            message: message.to_string(),
+            offset: self.pos,
+            line,
+            column,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), JsoncError> {
+        match skip_trivia(self.text, self.pos) {
+            Ok(pos) => {
+                self.pos = pos;
+                Ok(())
+            }
+            Err(pos) => {
+                self.pos = pos;
+                Err(self.error("Unterminated block comment"))
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonNode, JsoncError> {
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => {
+                let (value, span) = self.parse_string()?;
+                Ok(JsonNode::Scalar {
+                    span,
+                    value: Value::String(value),
+                })
+            }
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b't' | b'f' | b'n') => self.parse_literal(),
+            Some(_) => Err(self.error("Expected a value")),
+            None => Err(self.error("Unexpected end of file")),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonNode, JsoncError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b'}') => break,
+                Some(b'"') => {}
+                Some(_) => return Err(self.error("Expected a string key or '}'")),
+                None => return Err(self.error("Unexpected end of file")),
+            }
+            let (key, key_span) = self.parse_string()?;
+            self.skip_trivia()?;
+            if self.peek() != Some(b':') {
+                return Err(self.error("Expected ':'"));
+            }
+            self.pos += 1;
+            self.skip_trivia()?;
+            let value = self.parse_value()?;
+            members.push(JsonMember {
+                key,
+                key_span,
+                value,
+            });
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => break,
+                Some(_) => return Err(self.error("Expected ',' or '}'")),
+                None => return Err(self.error("Unexpected end of file")),
+            }
+        }
+        self.pos += 1;
+        Ok(JsonNode::Object {
+            span: start..self.pos,
+            members,
+        })
+    }
+
+    fn parse_array(&mut self) -> Result<JsonNode, JsoncError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(b']') {
+                break;
+            }
+            items.push(self.parse_value()?);
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => break,
+                Some(_) => return Err(self.error("Expected ',' or ']'")),
+                None => return Err(self.error("Unexpected end of file")),
+            }
+        }
+        self.pos += 1;
+        Ok(JsonNode::Array {
+            span: start..self.pos,
+            items,
+        })
+    }
+
+    fn parse_string(&mut self) -> Result<(String, Span), JsoncError> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        let mut end = start + 1;
+        loop {
+            match bytes.get(end) {
+                Some(b'"') => break,
+                Some(b'\\') => end += 2,
+                Some(b'\n') | None => {
+                    self.pos = start;
+                    return Err(self.error("Unterminated string"));
+                }
+                Some(_) => end += 1,
+            }
+        }
+        end += 1;
+        let value = serde_json::from_str::<String>(&self.text[start..end]).map_err(|_| {
+            self.pos = start;
+            self.error("Invalid string")
+        })?;
+        self.pos = end;
+        Ok((value, start..end))
+    }
+
+    fn parse_number(&mut self) -> Result<JsonNode, JsoncError> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        let mut end = start;
+        while matches!(
+            bytes.get(end),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        ) {
+            end += 1;
+        }
+        let value = serde_json::from_str::<Value>(&self.text[start..end])
+            .map_err(|_| self.error("Invalid number"))?;
+        self.pos = end;
+        Ok(JsonNode::Scalar {
+            span: start..end,
+            value,
+        })
+    }
+
+    fn parse_literal(&mut self) -> Result<JsonNode, JsoncError> {
+        let start = self.pos;
+        for (literal, value) in [
+            ("true", Value::Bool(true)),
+            ("false", Value::Bool(false)),
+            ("null", Value::Null),
+        ] {
+            if self.text[start..].starts_with(literal) {
+                self.pos += literal.len();
+                return Ok(JsonNode::Scalar {
+                    span: start..self.pos,
+                    value,
+                });
+            }
+        }
+        Err(self.error("Expected a value"))
+    }
+}
+
+/// Skips whitespace and comments from `pos`. On an unterminated block comment, returns its start.
+fn skip_trivia(text: &str, mut pos: usize) -> Result<usize, usize> {
+    let bytes = text.as_bytes();
+    loop {
+        match bytes.get(pos) {
+            Some(b' ' | b'\t' | b'\n' | b'\r') => pos += 1,
+            Some(b'/') if bytes.get(pos + 1) == Some(&b'/') => {
+                pos = text[pos..].find('\n').map_or(text.len(), |i| pos + i);
+            }
+            Some(b'/') if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = text[pos + 2..]
+                    .find("*/")
+                    .map(|i| pos + 2 + i + 2)
+                    .ok_or(pos)?;
+            }
+            _ => return Ok(pos),
+        }
+    }
+}
+
+/// The formatting conventions of an existing document, used for the text we insert into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    pub indent: String,
+    pub eol: String,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            indent: "  ".to_string(),
+            eol: "\n".to_string(),
+        }
+    }
+}
+
+impl Style {
+    pub fn detect(text: &str, root: Option<&JsonNode>) -> Style {
+        let mut style = Style::default();
+        if text.contains("\r\n") {
+            style.eol = "\r\n".to_string();
+        }
+        let first_member = root.and_then(|r| r.members().first());
+        if let Some(member) = first_member.filter(|m| is_first_on_line(text, m.key_span.start)) {
+            let indent = line_indent(text, member.key_span.start);
+            if !indent.is_empty() {
+                style.indent = indent.to_string();
+            }
+        }
+        style
+    }
+
+    /// Serializes `value` as it would appear at a position whose line is indented by `base`.
+    pub fn to_json(&self, value: &Value, base: &str) -> String {
+        let mut buffer = Vec::new();
+        let formatter = PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut serializer = Serializer::with_formatter(&mut buffer, formatter);
+        value
+            .serialize(&mut serializer)
+            .expect("serializing a JSON value cannot fail");
+        let pretty = String::from_utf8(buffer).expect("serde_json writes valid UTF-8");
+        pretty.replace('\n', &format!("{}{}", self.eol, base))
+    }
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Position of the line break ending the line of `pos` (before `\r\n`), or the end of the text.
+fn line_end(text: &str, pos: usize) -> usize {
+    match text[pos..].find('\n') {
+        Some(i) if i > 0 && text.as_bytes()[pos + i - 1] == b'\r' => pos + i - 1,
+        Some(i) => pos + i,
+        None => text.len(),
+    }
+}
+
+fn line_indent(text: &str, pos: usize) -> &str {
+    let start = line_start(text, pos);
+    let line = &text[start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+fn is_first_on_line(text: &str, pos: usize) -> bool {
+    text[line_start(text, pos)..pos].trim().is_empty()
+}
+
+/// Whether the rest of the line after `pos` holds nothing but whitespace or a `//` comment.
+fn rest_of_line_is_trivia(text: &str, pos: usize) -> bool {
+    let rest = text[pos..line_end(text, pos)].trim();
+    rest.is_empty() || rest.starts_with("//")
+}
+
+/// Position of the comma following a value that ends at `pos`, if there is one.
+fn comma_after(text: &str, pos: usize) -> Option<usize> {
+    let next = skip_trivia(text, pos).ok()?;
+    (text.as_bytes().get(next) == Some(&b',')).then_some(next)
+}
+
+/// Applies edits back to front. Insertions at the same position end up in the order given.
+fn apply_edits(text: &str, edits: Vec<(Span, String)>) -> String {
+    let mut edits: Vec<_> = edits.into_iter().enumerate().collect();
+    edits.sort_by_key(|(index, (span, _))| std::cmp::Reverse((span.start, *index)));
+    let edits = edits.into_iter().map(|(_, edit)| edit);
+    let mut result = text.to_string();
+    for (span, replacement) in edits {
+        result.replace_range(span, &replacement);
+    }
+    result
+}
+
+fn member_text(key: &str, value: &str) -> String {
+    format!("{}: {}", Value::String(key.to_string()), value)
+}
+
+/// Spacing of a value written on one line, e.g. `["a", "b"]` or `{ "a": 1 }`.
+struct CompactSpacing {
+    /// After `,`
+    comma: String,
+    /// After `:`
+    colon: String,
+    /// Inside the brackets
+    padding: String,
+}
+
+impl CompactSpacing {
+    /// The spacing around `member` and inside its value, `", "` and `": "` when there is none.
+    fn of(text: &str, member: &JsonMember) -> CompactSpacing {
+        let value = member.value.span();
+        let after = |pos: usize| {
+            let rest = &text[pos..];
+            rest[..rest.len() - rest.trim_start_matches([' ', '\t']).len()].to_string()
+        };
+        let colon = text[member.key_span.end..value.start]
+            .find(':')
+            .map_or(" ".to_string(), |i| after(member.key_span.end + i + 1));
+        // Where each item or member of the value starts and ends
+        let items: Vec<(usize, usize)> = match &member.value {
+            JsonNode::Array { items, .. } => items
+                .iter()
+                .map(|item| (item.span().start, item.span().end))
+                .collect(),
+            JsonNode::Object { members, .. } => members
+                .iter()
+                .map(|m| (m.key_span.start, m.value.span().end))
+                .collect(),
+            JsonNode::Scalar { .. } => Vec::new(),
+        };
+        let comma = match items.as_slice() {
+            [(_, end), (next, _), ..] if !text[*end..*next].contains('\n') => {
+                comma_after(text, *end).map(|comma| after(comma + 1))
+            }
+            _ => comma_after(text, value.end)
+                .filter(|&comma| !rest_of_line_is_trivia(text, comma + 1))
+                .map(|comma| after(comma + 1)),
+        };
+        let padding = match items.first() {
+            Some(&(start, _)) if !text[value.start + 1..start].contains('\n') => {
+                after(value.start + 1)
+            }
+            _ => String::new(),
+        };
+        CompactSpacing {
+            comma: comma.unwrap_or_else(|| " ".to_string()),
+            colon,
+            padding,
+        }
+    }
+
+    fn to_json(&self, value: &Value) -> String {
+        let join = |items: Vec<String>| items.join(&format!(",{}", self.comma));
+        match value {
+            Value::Array(items) if !items.is_empty() => {
+                let items = items.iter().map(|item| self.to_json(item)).collect();
+                format!("[{pad}{}{pad}]", join(items), pad = self.padding)
+            }
+            Value::Object(members) if !members.is_empty() => {
+                let members = members
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = Value::String(key.clone());
+                        format!("{}:{}{}", key, self.colon, self.to_json(value))
+                    })
+                    .collect();
+                format!("{{{pad}{}{pad}}}", join(members), pad = self.padding)
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Replaces the value of `member`, leaving everything around it untouched.
+pub fn replace_value(text: &str, member: &JsonMember, value: &Value, style: &Style) -> String {
+    // Members sharing a line with their siblings, e.g. `{"a": 1, "b": 2}`, and non-empty
+    // arrays or objects written on one line stay compact
+    let one_line = match &member.value {
+        JsonNode::Array { items, span } if !items.is_empty() => !text[span.clone()].contains('\n'),
+        JsonNode::Object { members, span } if !members.is_empty() => {
+            !text[span.clone()].contains('\n')
+        }
+        _ => false,
+    };
+    let json = if is_first_on_line(text, member.key_span.start) && !one_line {
+        style.to_json(value, line_indent(text, member.key_span.start))
+    } else {
+        CompactSpacing::of(text, member).to_json(value)
+    };
+    apply_edits(text, vec![(member.value.span(), json)])
+}
+
+pub fn rename_member(text: &str, member: &JsonMember, new_key: &str) -> String {
+    apply_edits(
+        text,
+        vec![(
+            member.key_span.clone(),
+            Value::String(new_key.to_string()).to_string(),
+        )],
+    )
+}
+
+/// Appends a member to the end of `object`, following the layout of its existing members.
+pub fn insert_member(
+    text: &str,
+    object: &JsonNode,
+    key: &str,
+    value: &Value,
+    style: &Style,
+) -> String {
+    let span = object.span();
+    let parent_indent = line_indent(text, span.start).to_string();
+    let Some(last) = object.members().last() else {
+        // Empty object: lay it out over several lines
+        let indent = format!("{}{}", parent_indent, style.indent);
+        let member = member_text(key, &style.to_json(value, &indent));
+        let inner = span.start + 1..span.end - 1;
+        let content_end = inner.start + text[inner.clone()].trim_end().len();
+        return apply_edits(
+            text,
+            vec![(
+                content_end..inner.end,
+                format!("{eol}{indent}{member}{eol}{parent_indent}", eol = style.eol),
+            )],
+        );
+    };
+
+    let value_end = last.value.span().end;
+    if !text[span.clone()].contains('\n') {
+        let member = member_text(key, &CompactSpacing::of(text, last).to_json(value));
+        return apply_edits(text, vec![(value_end..value_end, format!(", {}", member))]);
+    }
+
+    let indent = if is_first_on_line(text, last.key_span.start) {
+        line_indent(text, last.key_span.start).to_string()
+    } else {
+        format!("{}{}", parent_indent, style.indent)
+    };
+    let member = member_text(key, &style.to_json(value, &indent));
+    let mut edits = Vec::new();
+    let (anchor, suffix) = match comma_after(text, value_end) {
+        // Keep the trailing comma style of the file
+        Some(comma) => (comma + 1, ","),
+        None => {
+            edits.push((value_end..value_end, ",".to_string()));
+            (value_end, "")
+        }
+    };
+    let at = if rest_of_line_is_trivia(text, anchor) {
+        line_end(text, anchor)
+    } else {
+        anchor
+    };
+    edits.push((
+        at..at,
+        format!("{}{}{}{}", style.eol, indent, member, suffix),
+    ));
+    apply_edits(text, edits)
+}
+
+/// Inserts a member before the member at `index` of `object`, on its own line when that one is.
+pub fn insert_member_before(
+    text: &str,
+    object: &JsonNode,
+    index: usize,
+    key: &str,
+    value: &Value,
+    style: &Style,
+) -> String {
+    let next = &object.members()[index];
+    let start = next.key_span.start;
+    if !is_first_on_line(text, start) {
+        let member = member_text(key, &value.to_string());
+        return apply_edits(text, vec![(start..start, format!("{}, ", member))]);
+    }
+    let indent = line_indent(text, start).to_string();
+    let member = member_text(key, &style.to_json(value, &indent));
+    // Comment lines right above the next member stay attached to it
+    let mut at = line_start(text, start);
+    while at > 0 {
+        let previous = line_start(text, at - 1);
+        if !text[previous..at].trim_start().starts_with("//") {
+            break;
+        }
+        at = previous;
+    }
+    apply_edits(
+        text,
+        vec![(at..at, format!("{}{},{}", indent, member, style.eol))],
+    )
+}
+
+/// The `//` and `/* */` comments within `span`, strings excluded.
+pub fn comments(text: &str, span: Span) -> Vec<&str> {
+    let mut comments = Vec::new();
+    let bytes = text.as_bytes();
+    let mut pos = span.start;
+    while pos < span.end {
+        match bytes[pos] {
+            b'"' => {
+                pos += 1;
+                while pos < span.end && bytes[pos] != b'"' {
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos += 1;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                let end = text[pos..span.end].find('\n').map_or(span.end, |i| pos + i);
+                comments.push(text[pos..end].trim_end());
+                pos = end;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                let end = text[pos + 2..span.end]
+                    .find("*/")
+                    .map_or(span.end, |i| pos + 2 + i + 2);
+                comments.push(&text[pos..end]);
+                pos = end;
+            }
+            _ => pos += 1,
+        }
+    }
+    comments
+}
+
+/// Removes the member at `index` of `object` together with its comma and, when it sits on its
+/// own line, the whole line.
+pub fn remove_member(text: &str, object: &JsonNode, index: usize) -> String {
+    let members = object.members();
+    let member = &members[index];
+    let own_line = is_first_on_line(text, member.key_span.start);
+    let mut start = member.key_span.start;
+    let mut end = member.value.span().end;
+
+    if let Some(comma) = comma_after(text, end) {
+        end = comma + 1;
+        if own_line && rest_of_line_is_trivia(text, end) {
+            start = line_start(text, start);
+            end = text[end..].find('\n').map_or(text.len(), |i| end + i + 1);
+        } else {
+            end += text[end..].len() - text[end..].trim_start_matches([' ', '\t']).len();
+        }
+        return apply_edits(text, vec![(start..end, String::new())]);
+    }
+
+    if own_line && rest_of_line_is_trivia(text, end) {
+        end = line_end(text, end);
+    }
+    let mut edits = Vec::new();
+    match index.checked_sub(1).map(|i| &members[i]) {
+        Some(previous) => {
+            let comma = comma_after(text, previous.value.span().end)
+                .expect("members are separated by commas");
+            if own_line {
+                start = line_start(text, start);
+                start = text[..start].trim_end_matches(['\n', '\r']).len();
+                edits.push((comma..comma + 1, String::new()));
+            } else {
+                start = comma;
+            }
+        }
+        None if own_line => {
+            start = line_start(text, start);
+            start = text[..start].trim_end_matches(['\n', '\r']).len();
+        }
+        None => {}
+    }
+    edits.push((start..end, String::new()));
+    apply_edits(text, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_insert_member_before_and_comments() {
+        let text = "{\n  // first\n  \"b\": 1, /* two */\n  \"c\": \"// not a comment\"\n}";
+        let root = parse(text).unwrap();
+
+        let inserted = insert_member_before(text, &root, 0, "a", &json!(0), &Style::default());
+        assert_eq!(
+            inserted,
+            "{\n  \"a\": 0,\n  // first\n  \"b\": 1, /* two */\n  \"c\": \"// not a comment\"\n}"
+        );
+        assert_eq!(comments(text, root.span()), vec!["// first", "/* two */"]);
+    }
+
+    #[test]
+    fn test_parse_comments_and_trailing_commas() {
+        let text = r#"{
+  // line comment
+  "a": [1, 2, /* inline */ 3,],
+  "b": { "c": "d", },
+}"#;
+        let root = parse(text).unwrap();
+
+        assert_eq!(
+            root.to_value(),
+            json!({ "a": [1, 2, 3], "b": { "c": "d" } })
+        );
+        let member = root.member("b").unwrap();
+        assert_eq!(&text[member.key_span.clone()], r#""b""#);
+        assert_eq!(&text[member.value.span()], r#"{ "c": "d", }"#);
+    }
+
+    #[test]
+    fn test_parse_error_reports_location() {
+        let error = parse("{\n  \"a\": 1\n  \"b\": 2\n}").unwrap_err();
+        assert_eq!(error.to_string(), "Expected ',' or '}' at line 3 column 3");
+    }
+
+    #[test]
+    fn test_insert_member_keeps_layout() {
+        let text = "{\r\n\t// keep me\r\n\t\"a\": 1 // and me\r\n}\r\n";
+        let root = parse(text).unwrap();
+        let style = Style::detect(text, Some(&root));
+
+        let result = insert_member(text, &root, "b", &json!({ "x": 2 }), &style);
+        assert_eq!(
+            result,
+            "{\r\n\t// keep me\r\n\t\"a\": 1, // and me\r\n\t\"b\": {\r\n\t\t\"x\": 2\r\n\t}\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_member_after_multi_line_value() {
+        let text = "{\n  \"a\": {\n    \"x\": 1\n  }\n}";
+        let root = parse(text).unwrap();
+
+        let result = insert_member(text, &root, "b", &json!(2), &Style::default());
+        assert_eq!(result, "{\n  \"a\": {\n    \"x\": 1\n  },\n  \"b\": 2\n}");
+    }
+
+    #[test]
+    fn test_insert_member_keeps_trailing_comma() {
+        let text = "{\n  \"a\": 1,\n}";
+        let root = parse(text).unwrap();
+
+        let result = insert_member(text, &root, "b", &json!(2), &Style::default());
+        assert_eq!(result, "{\n  \"a\": 1,\n  \"b\": 2,\n}");
+    }
+
+    #[test]
+    fn test_insert_member_into_empty_object() {
+        let root = parse("{}").unwrap();
+        let result = insert_member("{}", &root, "a", &json!(["x"]), &Style::default());
+        assert_eq!(result, "{\n  \"a\": [\n    \"x\"\n  ]\n}");
+    }
+
+    #[test]
+    fn test_remove_member() {
+        let text = "{\n  \"a\": 1,\n  // about b\n  \"b\": 2, // b\n  \"c\": 3\n}";
+        let root = parse(text).unwrap();
+
+        assert_eq!(
+            remove_member(text, &root, 1),
+            "{\n  \"a\": 1,\n  // about b\n  \"c\": 3\n}"
+        );
+        assert_eq!(
+            remove_member(text, &root, 2),
+            "{\n  \"a\": 1,\n  // about b\n  \"b\": 2 // b\n}"
+        );
+        assert_eq!(
+            remove_member(
+                r#"{"a": 1, "b": 2}"#,
+                &parse(r#"{"a": 1, "b": 2}"#).unwrap(),
+                0
+            ),
+            r#"{"b": 2}"#
+        );
+    }
+
+    #[test]
+    fn test_replace_value_and_rename_member() {
+        let text = "{\n    \"a\": [\"old\"], \"b\": true\n}";
+        let root = parse(text).unwrap();
+        let style = Style::detect(text, Some(&root));
+
+        let replaced = replace_value(text, root.member("b").unwrap(), &json!(false), &style);
+        assert_eq!(replaced, "{\n    \"a\": [\"old\"], \"b\": false\n}");
+
+        let renamed = rename_member(text, root.member("a").unwrap(), "z");
+        assert_eq!(renamed, "{\n    \"z\": [\"old\"], \"b\": true\n}");
+
+        // Compact values keep their spacing
+        let replaced = replace_value(text, root.member("a").unwrap(), &json!(["x", "y"]), &style);
+        assert_eq!(replaced, "{\n    \"a\": [\"x\", \"y\"], \"b\": true\n}");
+        let text = r#"{ "s": { "body": [ "a",  "b" ], "prefix": "p" } }"#;
+        let root = parse(text).unwrap();
+        let snippet = &root.member("s").unwrap().value;
+        let replaced = replace_value(
+            text,
+            snippet.member("body").unwrap(),
+            &json!(["c", "d", "e"]),
+            &style,
+        );
+        assert_eq!(
+            replaced,
+            r#"{ "s": { "body": [ "c",  "d",  "e" ], "prefix": "p" } }"#
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use crate::helpers::jsonc::{line_col, JsonMember, JsonNode};
+use crate::helpers::resolve_variable::resolve_variable;
+use crate::models::expand_context_model::ExpandContext;
+use crate::models::lint_finding_model::LintFinding;
+use crate::models::lint_rule_model::{LintLevel, LintLevels, LintRule};
+use crate::models::snippet_document_model::SnippetDocument;
+use crate::models::snippet_model::Snippet;
+use crate::parser::print_snippet_ast::print_snippet_ast;
+use crate::parser::snippet_ast::{Node, SnippetAst};
+use std::collections::{BTreeMap, HashMap};
+
+/// Runs the lint rules that aren't allowed on a collection file, findings are in file order.
+pub fn lint_snippet_document(
+    path: &str,
+    document: &SnippetDocument,
+    levels: &LintLevels,
+) -> Vec<LintFinding> {
+    let Some(root) = document.root() else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+    let mut report = |rule: LintRule, key: &str, offset: usize, message: String| {
+        let level = levels.level(rule);
+        if level == LintLevel::Allow {
+            return;
+        }
+        let (line, column) = line_col(document.text(), offset);
+        findings.push(LintFinding {
+            rule,
+            level,
+            path: path.to_string(),
+            key: key.to_string(),
+            line,
+            column,
+            message,
+            fixable: rule.is_fixable(),
+        });
+    };
+
+    // serde keeps the last entry of a key, the earlier ones are silently dropped
+    let members = root.members();
+    for (index, member) in members.iter().enumerate() {
+        if let Some(later) = members[index + 1..].iter().find(|m| m.key == member.key) {
+            let (line, _) = line_col(document.text(), later.key_span.start);
+            report(
+                LintRule::DuplicateKey,
+                &member.key,
+                member.key_span.start,
+                format!(
+                    "Key '{}' is defined again at line {}, this entry is ignored",
+                    member.key, line
+                ),
+            );
+        }
+    }
+
+    let mut prefixes: HashMap<String, (&str, &str)> = HashMap::new();
+    for (key, snippet) in document.iter() {
+        let member = root.member(key).expect("every snippet has a member");
+        let field = |name: &str| {
+            member
+                .value
+                .member(name)
+                .map_or(member.key_span.start, |field| field.value.span().start)
+        };
+
+        for prefix in snippet.prefixes() {
+            let message = match prefixes.get(&prefix.to_lowercase()) {
+                Some((other_key, other)) if *other == prefix => {
+                    format!("Prefix '{}' is also used by '{}'", prefix, other_key)
+                }
+                Some((other_key, other)) => format!(
+                    "Prefix '{}' only differs in case from '{}' of '{}'",
+                    prefix, other, other_key
+                ),
+                None => {
+                    prefixes.insert(prefix.to_lowercase(), (key, prefix));
+                    continue;
+                }
+            };
+            report(LintRule::DuplicatePrefix, key, field("prefix"), message);
+        }
+
+        if snippet.body_text().trim().is_empty() {
+            report(
+                LintRule::EmptyBody,
+                key,
+                field("body"),
+                "Body is empty".to_string(),
+            );
+        }
+        if snippet.description().trim().is_empty() {
+            report(
+                LintRule::EmptyDescription,
+                key,
+                field("description"),
+                "Description is missing or empty".to_string(),
+            );
+        }
+
+        let body_text = snippet.body_text();
+        let body_offset = |offset: usize| body_position(member, snippet, offset);
+        let ast = snippet.body_ast();
+
+        let indices: Vec<u32> = ast.tabstops().into_iter().filter(|&i| i != 0).collect();
+        let missing: Vec<String> = (1..indices.last().copied().unwrap_or_default())
+            .filter(|index| !indices.contains(index))
+            .map(|index| format!("${}", index))
+            .collect();
+        if !missing.is_empty() {
+            report(
+                LintRule::TabstopGap,
+                key,
+                field("body"),
+                format!("Tabstops skip {}", missing.join(", ")),
+            );
+        }
+
+        let mut defaults: BTreeMap<u32, String> = BTreeMap::new();
+        for node in ast.walk() {
+            let default = match node {
+                Node::Placeholder { children, .. } => print_snippet_ast(&SnippetAst {
+                    nodes: children.clone(),
+                }),
+                Node::Choice { options, .. } => options
+                    .iter()
+                    .map(|option| option.raw.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                _ => continue,
+            };
+            let index = node.index().expect("placeholders have an index");
+            match defaults.get(&index) {
+                Some(first) if *first != default => report(
+                    LintRule::ConflictingDefaults,
+                    key,
+                    body_offset(node.span().start),
+                    format!(
+                        "${} defaults to '{}' here but to '{}' earlier",
+                        index, default, first
+                    ),
+                ),
+                Some(_) => {}
+                None => {
+                    defaults.insert(index, default);
+                }
+            }
+        }
+
+        for node in ast.walk() {
+            match node {
+                Node::Text { text, span } => {
+                    for offset in unescaped(&text.raw) {
+                        report(
+                            LintRule::UnescapedCharacter,
+                            key,
+                            body_offset(span.start + offset),
+                            format!(
+                                "'{}' should be escaped as '\\{}'",
+                                &text.raw[offset..offset + 1],
+                                &text.raw[offset..offset + 1]
+                            ),
+                        );
+                    }
+                }
+                Node::Variable { name, span, .. }
+                    if name != "env"
+                        && resolve_variable(name, &ExpandContext::default()).is_none() =>
+                {
+                    report(
+                        LintRule::UnknownVariable,
+                        key,
+                        body_offset(span.start),
+                        format!(
+                            "Unknown variable ${}, escape it as \\${} if it is literal",
+                            name, name
+                        ),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let mut start = 0;
+        for line in body_text.split('\n') {
+            if line.len() != line.trim_end().len() {
+                report(
+                    LintRule::TrailingWhitespace,
+                    key,
+                    body_offset(start),
+                    "Body line ends with whitespace".to_string(),
+                );
+            }
+            start += line.len() + 1;
+        }
+    }
+
+    findings.sort_by_key(|finding| (finding.line, finding.column));
+    findings
+}
+
+/// Offsets of the `$` of a text that aren't escaped, and of the unescaped `}` after one.
+///
+/// Outside placeholders a `}` is literal, so code braces are only reported when they likely
+/// belong to a broken `${...}`.
+pub fn unescaped(raw: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut chars = raw.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '$' => offsets.push(offset),
+            '}' if !offsets.is_empty() => offsets.push(offset),
+            _ => {}
+        }
+    }
+    offsets
+}
+
+/// The position in the file of the body entry holding an offset of `body_text`.
+fn body_position(member: &JsonMember, snippet: &Snippet, offset: usize) -> usize {
+    let Some(body) = member.value.member("body") else {
+        return member.key_span.start;
+    };
+    let JsonNode::Array { items, .. } = &body.value else {
+        return body.value.span().start;
+    };
+    let mut end = 0;
+    for (line, item) in snippet.body.iter().zip(items) {
+        end += line.len() + 1;
+        if offset < end {
+            return item.span().start;
+        }
+    }
+    body.value.span().start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"{
+  "a": {
+    "prefix": ["log", "Log"],
+    "body": ["console.log($3); ", "cost: $ 5 }", "${1:x} ${1:y} $FOO"]
+  },
+  "b": { "prefix": "log", "body": "", "description": "" },
+  "a": { "prefix": "x", "body": "x", "description": "X" }
+}"#;
+
+    #[test]
+    fn test_lint_snippet_document() {
+        let document = SnippetDocument::parse(TEXT.to_string()).unwrap();
+        let findings = lint_snippet_document("s.json", &document, &LintLevels::default());
+        let found: Vec<(LintRule, &str, usize, usize)> = findings
+            .iter()
+            .map(|f| (f.rule, f.key.as_str(), f.line, f.column))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (LintRule::DuplicateKey, "a", 2, 3),
+                (LintRule::EmptyBody, "b", 6, 35),
+                (LintRule::EmptyDescription, "b", 6, 54),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_snippet_body() {
+        let document = SnippetDocument::parse(
+            TEXT.replace("\"a\": { \"prefix\": \"x\"", "\"c\": { \"prefix\": \"x\""),
+        )
+        .unwrap();
+        let mut levels = LintLevels::default();
+        levels.set(&[LintRule::EmptyDescription], LintLevel::Allow);
+        let findings = lint_snippet_document("s.json", &document, &levels);
+        let found: Vec<(LintRule, &str, usize, usize)> = findings
+            .iter()
+            .map(|f| (f.rule, f.key.as_str(), f.line, f.column))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (LintRule::DuplicatePrefix, "a", 3, 15),
+                (LintRule::TabstopGap, "a", 4, 13),
+                (LintRule::TrailingWhitespace, "a", 4, 14),
+                (LintRule::UnescapedCharacter, "a", 4, 35),
+                (LintRule::UnescapedCharacter, "a", 4, 35),
+                (LintRule::ConflictingDefaults, "a", 4, 50),
+                (LintRule::UnknownVariable, "a", 4, 50),
+                (LintRule::DuplicatePrefix, "b", 6, 20),
+                (LintRule::EmptyBody, "b", 6, 35),
+            ]
+        );
+        assert!(findings[3].fixable);
+        assert_eq!(
+            findings[6].message,
+            "Unknown variable $FOO, escape it as \\$FOO if it is literal"
+        );
+    }
+}
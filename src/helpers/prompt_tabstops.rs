@@ -0,0 +1,102 @@
+use crate::models::expand_context_model::ExpandContext;
+use crate::parser::print_snippet_ast::print_snippet_ast;
+use crate::parser::render_snippet_ast::render_snippet_ast;
+use crate::parser::snippet_ast::{Node, SnippetAst};
+use anyhow::Context;
+use std::io::{BufRead, Write};
+
+/// Asks for the value of each tabstop in navigation order and stores the answers in the
+/// context, so mirrors of a tabstop reuse its value.
+///
+/// An empty answer (or the end of the input) keeps the default shown in brackets. Choices are
+/// listed and can be picked by number. Tabstops already given with `--set` and `$0` are skipped.
+pub fn prompt_tabstops(
+    ast: &SnippetAst,
+    context: &mut ExpandContext,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> anyhow::Result<()> {
+    for index in ast.tabstops() {
+        let key = index.to_string();
+        if index == 0 || context.values.contains_key(&key) {
+            continue;
+        }
+        let definition = ast.walk().into_iter().find(|node| {
+            node.index() == Some(index)
+                && matches!(node, Node::Placeholder { .. } | Node::Choice { .. })
+        });
+        // A placeholder set by its default text, e.g. `--set name=bar`, is already answered
+        if let Some(Node::Placeholder { children, .. }) = definition {
+            let name = print_snippet_ast(&SnippetAst {
+                nodes: children.clone(),
+            });
+            if context.values.contains_key(&name) {
+                continue;
+            }
+        }
+        let options: Vec<String> = match definition {
+            Some(Node::Choice { options, .. }) => {
+                options.iter().map(|option| option.value.clone()).collect()
+            }
+            _ => Vec::new(),
+        };
+        // Defaults are rendered with the earlier answers, e.g. `${2:$1_field}`
+        let default = match definition {
+            Some(Node::Placeholder { children, .. }) => {
+                let children = SnippetAst {
+                    nodes: children.clone(),
+                };
+                render_snippet_ast(&children, context).text
+            }
+            _ => options.first().cloned().unwrap_or_default(),
+        };
+
+        for (number, option) in options.iter().enumerate() {
+            writeln!(output, "  {}) {}", number + 1, option)?;
+        }
+        write!(output, "${} [{}]: ", index, default)?;
+        output.flush()?;
+
+        let mut answer = String::new();
+        input
+            .read_line(&mut answer)
+            .context("Failed to read the tabstop value")?;
+        let answer = answer.trim_end_matches(['\r', '\n']);
+        let value = match answer.parse::<usize>() {
+            _ if answer.is_empty() => default,
+            Ok(number) if (1..=options.len()).contains(&number) => options[number - 1].clone(),
+            _ => answer.to_string(),
+        };
+        context.values.insert(key, value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_snippet_body::parse_snippet_body;
+
+    #[test]
+    fn test_prompt_tabstops_in_order() -> anyhow::Result<()> {
+        let ast = parse_snippet_body(
+            "impl<${1:T}> Deref for ${2:Wrapper}<$1> { type Target = $1; ${3|a,b,c|} ${4:${2}_x} $0 }",
+        );
+        let mut context = ExpandContext::default();
+        let mut output = Vec::new();
+
+        prompt_tabstops(&ast, &mut context, &mut "U\n\n3\n".as_bytes(), &mut output)?;
+
+        assert_eq!(context.values["1"], "U");
+        assert_eq!(context.values["2"], "Wrapper");
+        assert_eq!(context.values["3"], "c");
+        // The input ended, so the default is kept
+        assert_eq!(context.values["4"], "Wrapper_x");
+        assert!(!context.values.contains_key("0"));
+        assert_eq!(
+            String::from_utf8(output)?,
+            "$1 [T]: $2 [Wrapper]:   1) a\n  2) b\n  3) c\n$3 [a]: $4 [Wrapper_x]: "
+        );
+        Ok(())
+    }
+}
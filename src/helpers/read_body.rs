@@ -0,0 +1,122 @@
+use crate::helpers::escape_snippet_text::escape_snippet_text;
+use crate::models::body_input_model::BodyInput;
+use anyhow::Context;
+use std::fs;
+use std::io::Read;
+
+/// Reads the body lines given with `--body-file` or `--stdin`, `None` when neither is given.
+///
+/// Lines are split on `\n` and `\r\n`, dedented with `--dedent`, and escaped so the code is
+/// inserted literally unless `--raw` is given.
+pub fn read_body(input: &BodyInput, stdin: &mut impl Read) -> anyhow::Result<Option<Vec<String>>> {
+    let text = match (&input.body_file, input.stdin) {
+        (Some(file), _) => fs::read_to_string(&file.path)
+            .with_context(|| format!("Failed to read {}", file.path.display()))?,
+        (None, true) => {
+            let mut text = String::new();
+            stdin
+                .read_to_string(&mut text)
+                .context("Failed to read the body from stdin")?;
+            text
+        }
+        (None, false) => return Ok(None),
+    };
+    let mut lines: Vec<&str> = text.lines().collect();
+
+    if let Some((start, end)) = input.body_file.as_ref().and_then(|file| file.lines) {
+        if end > lines.len() {
+            return Err(anyhow::anyhow!(
+                "Lines {}-{} are outside the file, which has {} lines",
+                start,
+                end,
+                lines.len()
+            ));
+        }
+        lines = lines[start - 1..end].to_vec();
+    }
+
+    if input.dedent {
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+            .min()
+            .unwrap_or_default();
+        // Blank lines may be shorter than the indentation
+        lines = lines
+            .into_iter()
+            .map(|line| line.get(indent..).unwrap_or_default())
+            .collect();
+    }
+
+    Ok(Some(
+        lines
+            .into_iter()
+            .map(|line| match input.raw {
+                true => line.to_string(),
+                false => escape_snippet_text(line),
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::body_input_model::BodyFile;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_read_body_from_line_range() -> anyhow::Result<()> {
+        let file = NamedTempFile::new()?;
+        fs::write(
+            file.path(),
+            "mod a {\r\n    fn f() {\r\n\r\n        format!(\"${x}\")\r\n    }\r\n}\r\n",
+        )?;
+        let spec = format!("{}:2-5", file.path().display());
+        let input = BodyInput {
+            body_file: Some(BodyFile::parse(&spec).unwrap()),
+            dedent: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            read_body(&input, &mut std::io::empty())?,
+            Some(vec![
+                "fn f() {".to_string(),
+                "".to_string(),
+                "    format!(\"\\${x\\}\")".to_string(),
+                "\\}".to_string(),
+            ])
+        );
+
+        let spec = format!("{}:5-9", file.path().display());
+        let input = BodyInput {
+            body_file: Some(BodyFile::parse(&spec).unwrap()),
+            ..Default::default()
+        };
+        assert!(read_body(&input, &mut std::io::empty()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_body_from_stdin() -> anyhow::Result<()> {
+        let input = BodyInput {
+            stdin: true,
+            raw: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            read_body(&input, &mut "let ${1:x} = 1;\n".as_bytes())?,
+            Some(vec!["let ${1:x} = 1;".to_string()])
+        );
+        assert_eq!(
+            read_body(&BodyInput::default(), &mut std::io::empty())?,
+            None
+        );
+        assert!(BodyFile::parse("a.rs:0-2").is_err());
+        assert_eq!(BodyFile::parse("C:\\a.rs").unwrap().lines, None);
+        assert_eq!(BodyFile::parse("a.rs:7").unwrap().lines, Some((7, 7)));
+        Ok(())
+    }
+}
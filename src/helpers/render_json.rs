@@ -0,0 +1,41 @@
+use crate::models::output_format_model::OutputFormat;
+use anyhow::Context;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Renders `value` for the JSON output formats: pretty-printed for `json`, and one compact
+/// line per element (or a single line for non-arrays) for `jsonl`.
+pub fn render_json<T: Serialize + ?Sized>(
+    value: &T,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let value = serde_json::to_value(value).context("Failed to serialize output")?;
+    match (format, value) {
+        (OutputFormat::Jsonl, Value::Array(items)) => Ok(items
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")),
+        (OutputFormat::Jsonl, value) => Ok(value.to_string()),
+        (_, value) => serde_json::to_string_pretty(&value).context("Failed to serialize output"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_json_lines() {
+        let records = vec![("a", 1), ("b", 2)];
+
+        assert_eq!(
+            render_json(&records, OutputFormat::Jsonl).unwrap(),
+            "[\"a\",1]\n[\"b\",2]"
+        );
+        assert_eq!(
+            render_json(&records[0], OutputFormat::Json).unwrap(),
+            "[\n  \"a\",\n  1\n]"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use crate::helpers::render_json::render_json;
+use crate::models::lint_finding_model::LintFinding;
+use crate::models::output_format_model::OutputFormat;
+use prettytable::{row, Table};
+
+/// Renders lint findings, in plain output as `path:line:column: level[rule] key: message`
+/// like compilers do, so editors and CI logs can link to them.
+pub fn render_lint_findings(
+    findings: &[LintFinding],
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => render_json(findings, format),
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Location", "Level", "Rule", "Key", "Message"]);
+            for finding in findings {
+                table.add_row(row![
+                    format!("{}:{}:{}", finding.path, finding.line, finding.column),
+                    finding.level,
+                    finding.rule,
+                    finding.key,
+                    finding.message
+                ]);
+            }
+            Ok(table.to_string())
+        }
+        OutputFormat::Plain => Ok(findings
+            .iter()
+            .map(|finding| {
+                format!(
+                    "{}:{}:{}: {}[{}] {}: {}{}\n",
+                    finding.path,
+                    finding.line,
+                    finding.column,
+                    finding.level,
+                    finding.rule,
+                    finding.key,
+                    finding.message,
+                    if finding.fixable { " (fixable)" } else { "" }
+                )
+            })
+            .collect()),
+    }
+}
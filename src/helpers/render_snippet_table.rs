@@ -0,0 +1,17 @@
+use crate::models::snippet_record_model::SnippetRecord;
+use prettytable::{row, Table};
+
+/// Renders snippets as a table with one row per snippet.
+pub fn render_snippet_table(records: &[SnippetRecord]) -> String {
+    let mut table = Table::new();
+    table.add_row(row!["Key", "Prefix", "Description", "Layer"]);
+    for record in records {
+        table.add_row(row![
+            record.key,
+            record.prefixes.join(", "),
+            record.description.as_deref().unwrap_or_default(),
+            record.layer
+        ]);
+    }
+    table.to_string()
+}
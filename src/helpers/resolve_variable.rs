@@ -0,0 +1,135 @@
+use crate::models::expand_context_model::ExpandContext;
+use std::path::Path;
+use uuid::Uuid;
+
+/// The value of a VSCode snippet variable, or `None` when the variable is unknown.
+///
+/// Variables about the editor state (`TM_SELECTED_TEXT`, `CLIPBOARD`, ...) are known but empty,
+/// so their default is used.
+pub fn resolve_variable(name: &str, context: &ExpandContext) -> Option<String> {
+    let now = &context.now;
+    let file = context.file.as_deref();
+    let file_name = |f: fn(&Path) -> Option<&std::ffi::OsStr>| {
+        file.and_then(f)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let value = match name {
+        "TM_SELECTED_TEXT" | "TM_CURRENT_LINE" | "TM_CURRENT_WORD" | "CLIPBOARD" => String::new(),
+        "TM_LINE_INDEX" => "0".to_string(),
+        "TM_LINE_NUMBER" => "1".to_string(),
+        "TM_FILENAME" => file_name(Path::file_name),
+        "TM_FILENAME_BASE" => file_name(Path::file_stem),
+        "TM_DIRECTORY" => file
+            .and_then(Path::parent)
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        "TM_FILEPATH" => file
+            .map(|file| file.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        "RELATIVE_FILEPATH" => file
+            .map(|file| {
+                context
+                    .workspace
+                    .as_deref()
+                    .and_then(|workspace| file.strip_prefix(workspace).ok())
+                    .unwrap_or(file)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .unwrap_or_default(),
+        "WORKSPACE_NAME" => context
+            .workspace
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        "WORKSPACE_FOLDER" => context
+            .workspace
+            .as_deref()
+            .map(|workspace| workspace.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        "CURRENT_YEAR" => now.format("%Y").to_string(),
+        "CURRENT_YEAR_SHORT" => now.format("%y").to_string(),
+        "CURRENT_MONTH" => now.format("%m").to_string(),
+        "CURRENT_MONTH_NAME" => now.format("%B").to_string(),
+        "CURRENT_MONTH_NAME_SHORT" => now.format("%b").to_string(),
+        "CURRENT_DATE" => now.format("%d").to_string(),
+        "CURRENT_DAY_NAME" => now.format("%A").to_string(),
+        "CURRENT_DAY_NAME_SHORT" => now.format("%a").to_string(),
+        "CURRENT_HOUR" => now.format("%H").to_string(),
+        "CURRENT_MINUTE" => now.format("%M").to_string(),
+        "CURRENT_SECOND" => now.format("%S").to_string(),
+        "CURRENT_SECONDS_UNIX" => now.timestamp().to_string(),
+        "CURRENT_TIMEZONE_OFFSET" => now.format("%:z").to_string(),
+        "RANDOM" => {
+            let bytes = Uuid::new_v4().into_bytes();
+            format!(
+                "{:06}",
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]) % 1_000_000
+            )
+        }
+        "RANDOM_HEX" => Uuid::new_v4().simple().to_string()[..6].to_string(),
+        "UUID" => Uuid::new_v4().to_string(),
+        "LINE_COMMENT" => comment_tokens(context.lang.as_deref()).0.to_string(),
+        "BLOCK_COMMENT_START" => comment_tokens(context.lang.as_deref()).1.to_string(),
+        "BLOCK_COMMENT_END" => comment_tokens(context.lang.as_deref()).2.to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Line comment, block comment start and end of a language.
+fn comment_tokens(lang: Option<&str>) -> (&'static str, &'static str, &'static str) {
+    match lang.unwrap_or_default() {
+        "python" | "shellscript" | "ruby" | "perl" | "r" | "yaml" | "toml" | "dockerfile"
+        | "makefile" | "powershell" => ("#", "", ""),
+        "html" | "xml" | "markdown" | "vue" | "svelte" => ("", "<!--", "-->"),
+        "css" => ("", "/*", "*/"),
+        "lua" => ("--", "--[[", "]]"),
+        "sql" | "haskell" => ("--", "/*", "*/"),
+        _ => ("//", "/*", "*/"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_resolve_file_and_date_variables() {
+        let context = ExpandContext {
+            file: Some(PathBuf::from("/work/app/src/main.rs")),
+            workspace: Some(PathBuf::from("/work/app")),
+            now: chrono::Local.with_ymd_and_hms(2024, 3, 5, 7, 8, 9).unwrap(),
+            ..Default::default()
+        };
+        let resolve = |name| resolve_variable(name, &context);
+
+        assert_eq!(resolve("TM_FILENAME").as_deref(), Some("main.rs"));
+        assert_eq!(resolve("TM_FILENAME_BASE").as_deref(), Some("main"));
+        assert_eq!(resolve("TM_DIRECTORY").as_deref(), Some("/work/app/src"));
+        assert_eq!(resolve("RELATIVE_FILEPATH").as_deref(), Some("src/main.rs"));
+        assert_eq!(resolve("WORKSPACE_NAME").as_deref(), Some("app"));
+        assert_eq!(resolve("CURRENT_YEAR").as_deref(), Some("2024"));
+        assert_eq!(resolve("CURRENT_MONTH_NAME_SHORT").as_deref(), Some("Mar"));
+        assert_eq!(resolve("CURRENT_DATE").as_deref(), Some("05"));
+        assert_eq!(resolve("LINE_COMMENT").as_deref(), Some("//"));
+        assert_eq!(resolve("TM_SELECTED_TEXT").as_deref(), Some(""));
+        assert_eq!(resolve("NOT_A_VARIABLE"), None);
+    }
+
+    #[test]
+    fn test_resolve_random_variables() {
+        let context = ExpandContext::default();
+
+        let uuid = resolve_variable("UUID", &context).unwrap();
+        assert!(Uuid::parse_str(&uuid).is_ok());
+        let hex = resolve_variable("RANDOM_HEX", &context).unwrap();
+        assert!(hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        let random = resolve_variable("RANDOM", &context).unwrap();
+        assert!(random.len() == 6 && random.chars().all(|c| c.is_ascii_digit()));
+    }
+}
@@ -0,0 +1,69 @@
+/// Splits text into lowercase search terms the way code is read: words end at anything but
+/// letters, digits and `_`, so `::` paths fall apart, and `snake_case` and `CamelCase`
+/// words give their parts too. `DerefMut` gives `derefmut`, `deref` and `mut`.
+pub fn tokenize_code(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+    {
+        let parts = split_word(word);
+        if parts.len() > 1 {
+            terms.push(word.to_lowercase());
+        }
+        terms.extend(parts);
+    }
+    terms
+}
+
+/// The `_` separated parts of `word`, with camelCase humps and acronyms split off, e.g.
+/// `HTTPServer` into `http` and `server`.
+fn split_word(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for segment in word.split('_').filter(|segment| !segment.is_empty()) {
+        let chars: Vec<char> = segment.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (previous, c) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            let hump = (previous.is_lowercase() || previous.is_numeric()) && c.is_uppercase();
+            let acronym_end = previous.is_uppercase() && c.is_uppercase() && next_is_lower;
+            if hump || acronym_end {
+                parts.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        parts.push(chars[start..].iter().collect::<String>().to_lowercase());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_code() {
+        assert_eq!(
+            tokenize_code("impl DerefMut for Foo {"),
+            vec!["impl", "derefmut", "deref", "mut", "for", "foo"]
+        );
+        assert_eq!(
+            tokenize_code("std::fmt::Display"),
+            vec!["std", "fmt", "display"]
+        );
+        assert_eq!(
+            tokenize_code("snake_case_key(HTTPServer, utf8)"),
+            vec![
+                "snake_case_key",
+                "snake",
+                "case",
+                "key",
+                "httpserver",
+                "http",
+                "server",
+                "utf8"
+            ]
+        );
+    }
+}
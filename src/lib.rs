@@ -2,3 +2,4 @@ pub mod actions;
 pub mod constants;
 pub mod helpers;
 pub mod models;
+pub mod parser;
@@ -1,109 +1,616 @@
 use anyhow::{self, Context, Result};
 use clap::Parser;
+use ratatui::crossterm::event;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{enable_raw_mode, EnterAlternateScreen};
 use snip_cli::actions::create_directory_and_file::create_directory_and_file;
-use snip_cli::actions::edit_snippet_in_file::edit_snippet_in_file;
+use snip_cli::actions::edit_snippet_interactively::edit_snippet_interactively;
+use snip_cli::actions::expand_snippet::expand_snippet;
+use snip_cli::actions::format_snippets::format_snippets;
+use snip_cli::actions::full_text_search::full_text_search;
+use snip_cli::actions::grep_snippets::grep_snippets;
+use snip_cli::actions::insert_snippet::insert_snippet;
+use snip_cli::actions::lint_snippets::lint_snippets;
+use snip_cli::actions::list_collections::list_collections;
 use snip_cli::actions::list_snippets::list_snippets;
 use snip_cli::actions::open_file_with::open_file_with;
 use snip_cli::actions::remove_snippet_from_file::remove_snippet_from_file;
+use snip_cli::actions::replay_change::{replay_change, Replay};
+use snip_cli::actions::run_tui::run_tui;
 use snip_cli::actions::search_snippets::search_snippets;
+use snip_cli::actions::show_history::show_history;
 use snip_cli::actions::show_snippet::show_snippet;
 use snip_cli::actions::update_key_in_file::update_key_in_file;
 use snip_cli::actions::write_snippet_to_file::write_snippet_to_file;
-use snip_cli::constants::DEFAULT_SNIPPET_PATH;
-use snip_cli::helpers::expand_home_dir::expand_home_dir;
+use snip_cli::helpers::copy_to_clipboard::copy_to_clipboard;
+use snip_cli::helpers::edit_snippet_template::edit_snippet_template;
 use snip_cli::helpers::get_app_config::get_app_config;
+use snip_cli::helpers::init_logger::init_logger;
+use snip_cli::helpers::prompt_tabstops::prompt_tabstops;
+use snip_cli::helpers::read_body::read_body;
+use snip_cli::helpers::render_json::render_json;
+use snip_cli::helpers::render_lint_findings::render_lint_findings;
 use snip_cli::models::cli_model::Cli;
-use snip_cli::models::commands_model::Commands;
+use snip_cli::models::collection_model::Collection;
+use snip_cli::models::collection_record_model::CollectionRecord;
+use snip_cli::models::commands_model::{Commands, ConfigCommands};
+use snip_cli::models::error_record_model::ErrorRecord;
+use snip_cli::models::expand_context_model::ExpandContext;
+use snip_cli::models::insert_position_model::InsertPosition;
+use snip_cli::models::journal_model::Journal;
+use snip_cli::models::layer_model::Layer;
+use snip_cli::models::layered_snippets_model::LayeredSnippets;
+use snip_cli::models::lint_rule_model::{LintLevel, LintLevels};
+use snip_cli::models::output_format_model::OutputFormat;
+use snip_cli::models::project_model::Project;
 use snip_cli::models::snip_config_model::SnipConfig;
+use snip_cli::models::snippet_template_model::SnippetTemplate;
+use snip_cli::models::tui_state_model::{TuiCollection, TuiState};
+use snip_cli::parser::snippet_ast::SnippetAst;
+use std::io::IsTerminal;
+use std::process::ExitCode;
+use std::{env, io, iter};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logger(cli.verbose, cli.quiet);
+    let json_errors = cli.output.is_some_and(|output| output.is_json());
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            // Scripts asking for JSON get errors in the same shape, on stderr
+            match render_json(&ErrorRecord::new(&error), OutputFormat::Jsonl) {
+                Ok(record) if json_errors => eprintln!("{}", record),
+                _ => eprintln!("Error: {:?}", error),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    // Human output picks the most readable format of each command
+    let format = |default: OutputFormat| cli.output.unwrap_or(default);
     let config_path = get_app_config();
 
     create_directory_and_file(&config_path)?;
 
-    let config = match SnipConfig::load(&config_path) {
-        Ok(cfg) => cfg,
-        Err(_) => {
-            let default_snippet_path = expand_home_dir(DEFAULT_SNIPPET_PATH)
-                .to_string_lossy()
-                .into_owned();
-            let new_config = SnipConfig {
-                path: default_snippet_path,
+    // Repairing and resetting must work on a config that no longer loads
+    if let Commands::Config { repair, reset, .. } = &cli.command {
+        if *repair || *reset {
+            let (config, backup) = if *repair {
+                SnipConfig::repair(&config_path)?
+            } else {
+                SnipConfig::reset(&config_path)?
             };
-            new_config.save(&config_path)?;
-            new_config
+            let (name, collection) = config.select(None, None)?;
+            if let Some(output) = cli.output.filter(OutputFormat::is_json) {
+                let record = CollectionRecord::new(name, collection, true);
+                println!("{}", render_json(&record, output)?);
+                return Ok(());
+            }
+            println!(
+                "Configuration rewritten with active collection '{}' at {}, previous file kept at {}",
+                name,
+                collection.path,
+                backup.display()
+            );
+            return Ok(());
         }
+    }
+
+    let config = SnipConfig::load_or_init(&config_path).with_context(|| {
+        format!(
+            "Failed to load the configuration, fix {} by hand or run `snip config --repair` to keep its snippet path or `snip config --reset` to start over",
+            config_path
+        )
+    })?;
+
+    let journal_path = Journal::path_for(&config_path);
+
+    // Only commands working on a collection need one to be selected, so `config add --lang`
+    // can name a language that has no collection yet
+    let selected = || -> Result<(String, Collection)> {
+        let (name, collection) = config.select(cli.collection.as_deref(), cli.lang.as_deref())?;
+        log::info!("Using collection '{}' at {}", name, collection.path);
+        Ok((name.to_string(), collection.clone()))
     };
+    let snippet_path = || selected().map(|(_, collection)| collection.path);
+    // Project snippets are found from the working directory, for the selected language
+    let project = || -> Result<(Project, Collection)> {
+        let (_, collection) = selected()?;
+        let current_dir = env::current_dir().context("Failed to read the current directory")?;
+        Ok((
+            Project::discover(&current_dir, &collection.lang),
+            collection,
+        ))
+    };
+    let sources = || project().map(|(project, collection)| project.sources(&collection.path));
 
-    let cli = Cli::parse();
     match cli.command {
         Commands::Add {
             key,
             prefix,
             description,
+            interactive,
+            scope,
+            layer,
+            body_input,
             body,
         } => {
-            dbg!(&key, &prefix, &description, &body);
-            write_snippet_to_file(&config.path, key, prefix, description, body)
-                .context("Failed to write snippet to file")?;
+            log::debug!("key={key:?} prefix={prefix:?} description={description:?} body={body:?}");
+            let body =
+                read_body(&body_input, &mut io::stdin())?.unwrap_or_else(|| split_lines(body));
+            // Without `--interactive`, clap requires the key and description
+            let template = SnippetTemplate {
+                key: key.unwrap_or_default(),
+                prefixes: prefix,
+                description: description.unwrap_or_default(),
+                scope: scope.unwrap_or_default(),
+                body,
+            };
+            let (project, collection) = project()?;
+            let template = match interactive {
+                true => edit_snippet_template(&template, &collection)?,
+                false => template,
+            };
+            let file_path = project.layer_path(
+                &collection.path,
+                &collection.lang,
+                layer.unwrap_or(Layer::User),
+            );
+            let change = write_snippet_to_file(
+                &file_path,
+                template.key,
+                template.prefixes,
+                template.description,
+                Some(template.scope).filter(|scope| !scope.is_empty()),
+                template.body,
+            )
+            .context("Failed to write snippet to file")?;
+            Journal::record(&journal_path, change)?;
         }
         Commands::Rm { key } => {
-            dbg!(&key);
-            remove_snippet_from_file(&config.path, &key)
+            log::debug!("key={key:?}");
+            let change = remove_snippet_from_file(&snippet_path()?, &key)
                 .context("Failed to remove snippet from file")?;
+            Journal::record(&journal_path, change)?;
         }
         Commands::Ls { list_option } => {
-            dbg!(list_option);
-            let output = list_snippets(&config.path, list_option)
-                .context("Failed to list snippets from file")?;
+            log::debug!("list_option={list_option:?}");
+            let output = list_snippets(
+                &sources()?,
+                list_option,
+                cli.lang.as_deref(),
+                format(OutputFormat::Plain),
+            )
+            .context("Failed to list snippets from file")?;
             println!("{}", output);
         }
         Commands::Edit {
             key,
+            name,
+            interactive,
             prefix,
             description,
+            scope,
+            layer,
+            body_input,
             body,
         } => {
-            dbg!(&key, &prefix, &description, &body);
-            edit_snippet_in_file(&config.path, key, prefix, description, body)
-                .context("Failed to edit snippet in file")?;
+            let key = key.or(name).context("A key is required")?;
+            log::debug!("key={key:?} prefix={prefix:?} description={description:?} body={body:?}");
+            let body = read_body(&body_input, &mut io::stdin())?.or(body.map(split_lines));
+            let (project, collection) = project()?;
+            let layered = LayeredSnippets::load(&project.sources(&collection.path))?;
+            let (snippet, source) = layered
+                .get(&key)
+                .ok_or_else(|| anyhow::anyhow!("Snippet with key '{}' not found", key))?;
+            let layer = layer.unwrap_or(source.layer);
+            let file_path = match layer == source.layer {
+                true => source.path.clone(),
+                false => project.layer_path(&collection.path, &collection.lang, layer),
+            };
+            // A user snippet edited in the project layer is overridden there
+            let copy = layer == Layer::Project && source.layer == Layer::User;
+            let changes =
+                edit_snippet_interactively(&file_path, &key, snippet, copy, |template| {
+                    match interactive {
+                        true => edit_snippet_template(template, &collection),
+                        false => Ok(SnippetTemplate {
+                            prefixes: prefix.unwrap_or_else(|| template.prefixes.clone()),
+                            description: description
+                                .unwrap_or_else(|| template.description.clone()),
+                            scope: scope.unwrap_or_else(|| template.scope.clone()),
+                            body: body.unwrap_or_else(|| template.body.clone()),
+                            ..template.clone()
+                        }),
+                    }
+                })?;
+            if changes.is_empty() {
+                println!("No changes made.");
+            }
+            for change in changes {
+                Journal::record(&journal_path, change)?;
+            }
         }
         Commands::Show { key } => {
-            dbg!(&key);
-            let output =
-                show_snippet(&config.path, key).context("Failed to show snippet from file")?;
+            log::debug!("key={key:?}");
+            let output = show_snippet(&sources()?, key, format(OutputFormat::Table))
+                .context("Failed to show snippet from file")?;
             println!("{}", output);
         }
-        Commands::Search { id, name } => {
-            dbg!(id, &name);
-            let output = search_snippets(&config.path, id, name)
-                .context("Failed to search snippet from file")?;
+        Commands::Expand {
+            name,
+            set,
+            file,
+            interactive,
+            copy,
+        } => {
+            log::debug!("name={name:?} set={set:?} file={file:?} interactive={interactive:?}");
+            let (project, collection) = project()?;
+            let context = ExpandContext {
+                values: set.into_iter().collect(),
+                file,
+                workspace: Some(project.root.clone()),
+                lang: Some(collection.lang.clone()),
+                ..Default::default()
+            };
+            // Prompts go to stderr so the result can be piped
+            let fill = |ast: &SnippetAst, context: &mut ExpandContext| match interactive {
+                true => prompt_tabstops(ast, context, &mut io::stdin().lock(), &mut io::stderr()),
+                false => Ok(()),
+            };
+            let output = expand_snippet(
+                &project.sources(&collection.path),
+                &name,
+                context,
+                fill,
+                format(OutputFormat::Plain),
+            )
+            .context("Failed to expand snippet")?;
+            if copy {
+                copy_to_clipboard(&output).context("Failed to copy the snippet")?;
+            } else {
+                println!("{}", output);
+            }
+        }
+        Commands::Insert {
+            name,
+            file,
+            line,
+            after_pattern,
+            set,
+            interactive,
+        } => {
+            log::debug!("name={name:?} file={file:?} line={line:?} after_pattern={after_pattern:?} set={set:?}");
+            let (project, collection) = project()?;
+            let position = match (line, after_pattern) {
+                (Some(line), _) => InsertPosition::Line(line),
+                (None, Some(pattern)) => InsertPosition::AfterPattern(pattern),
+                (None, None) => unreachable!("clap requires --line or --after-pattern"),
+            };
+            let context = ExpandContext {
+                values: set.into_iter().collect(),
+                file: Some(file.clone()),
+                workspace: Some(project.root.clone()),
+                lang: Some(collection.lang.clone()),
+                ..Default::default()
+            };
+            let fill = |ast: &SnippetAst, context: &mut ExpandContext| match interactive {
+                true => prompt_tabstops(ast, context, &mut io::stdin().lock(), &mut io::stderr()),
+                false => Ok(()),
+            };
+            let record = insert_snippet(
+                &project.sources(&collection.path),
+                &name,
+                &file,
+                &position,
+                context,
+                fill,
+            )
+            .context("Failed to insert snippet")?;
+            log::info!(
+                "Inserted '{}' at line {} of {}",
+                record.key,
+                record.line,
+                record.path
+            );
+            if let Some(output) = cli.output.filter(OutputFormat::is_json) {
+                println!("{}", render_json(&record, output)?);
+            }
+        }
+        Commands::Lint {
+            paths,
+            fix,
+            allow,
+            warn,
+            deny,
+        } => {
+            log::debug!("paths={paths:?} fix={fix:?} allow={allow:?} warn={warn:?} deny={deny:?}");
+            let paths = match paths.is_empty() {
+                true => sources()?.into_iter().map(|source| source.path).collect(),
+                false => paths,
+            };
+            let mut levels = LintLevels::default();
+            levels.set(&allow, LintLevel::Allow);
+            levels.set(&warn, LintLevel::Warn);
+            levels.set(&deny, LintLevel::Deny);
+            let report = lint_snippets(&paths, &levels, fix).context("Failed to lint snippets")?;
+            for change in report.changes.iter().cloned() {
+                Journal::record(&journal_path, change)?;
+            }
+            let output = render_lint_findings(&report.findings, format(OutputFormat::Plain))?;
+            if !output.is_empty() {
+                println!("{}", output.trim_end());
+            }
+            log::info!(
+                "{} problems found, {} fixed snippets",
+                report.findings.len(),
+                report.changes.len()
+            );
+            if report.errors() > 0 {
+                return Err(anyhow::anyhow!(
+                    "Lint failed, {} problem(s) found by denied rules",
+                    report.errors()
+                ));
+            }
+        }
+        Commands::Fmt {
+            paths,
+            check,
+            key_order,
+        } => {
+            log::debug!("paths={paths:?} check={check:?} key_order={key_order:?}");
+            let paths = match paths.is_empty() {
+                true => sources()?.into_iter().map(|source| source.path).collect(),
+                false => paths,
+            };
+            let key_order = key_order.unwrap_or(config.key_order);
+            let report =
+                format_snippets(&paths, key_order, check).context("Failed to format snippets")?;
+            for change in report.changes {
+                Journal::record(&journal_path, change)?;
+            }
+            let unformatted = report.unformatted;
+            if check {
+                for path in &unformatted {
+                    println!("{}", path);
+                }
+                if !unformatted.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "{} file(s) not formatted, run `snip fmt`",
+                        unformatted.len()
+                    ));
+                }
+            } else {
+                for path in &unformatted {
+                    log::info!("Formatted {}", path);
+                }
+            }
+        }
+        Commands::Tui => {
+            if !io::stdout().is_terminal() {
+                return Err(anyhow::anyhow!("`snip tui` needs a terminal"));
+            }
+            let (name, _) = selected()?;
+            let current_dir = env::current_dir().context("Failed to read the current directory")?;
+            // Every collection is offered, each with the project files of its language
+            let collections: Vec<TuiCollection> = config
+                .collections
+                .iter()
+                .map(|(name, collection)| TuiCollection {
+                    name: name.clone(),
+                    sources: Project::discover(&current_dir, &collection.lang)
+                        .sources(&collection.path),
+                    collection: collection.clone(),
+                })
+                .collect();
+            let current = collections
+                .iter()
+                .position(|collection| collection.name == name)
+                .unwrap_or_default();
+            let mut state = TuiState::new(collections, current);
 
-            for result in output {
-                println!("{}\n", result);
+            let mut terminal = ratatui::init();
+            // The editor gets the normal screen while it runs
+            let edit = |template: &SnippetTemplate, collection: &Collection| {
+                ratatui::restore();
+                let edited = edit_snippet_template(template, collection);
+                enable_raw_mode()?;
+                execute!(io::stdout(), EnterAlternateScreen)?;
+                edited
+            };
+            let events = iter::from_fn(|| Some(event::read()));
+            let result = run_tui(
+                &mut terminal,
+                &mut state,
+                events,
+                &journal_path,
+                edit,
+                copy_to_clipboard,
+            );
+            ratatui::restore();
+            result?;
+        }
+        Commands::Search {
+            id,
+            full_text,
+            name,
+        } => {
+            log::debug!("id={id:?} full_text={full_text:?} name={name:?}");
+            let highlight = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+            let output = match full_text {
+                true => full_text_search(
+                    &sources()?,
+                    &name,
+                    cli.lang.as_deref(),
+                    format(OutputFormat::Plain),
+                    highlight,
+                ),
+                false => search_snippets(
+                    &sources()?,
+                    id,
+                    name,
+                    cli.lang.as_deref(),
+                    format(OutputFormat::Plain),
+                    highlight,
+                ),
+            }
+            .context("Failed to search snippet from file")?;
+            println!("{}", output);
+        }
+        Commands::Grep {
+            pattern,
+            all,
+            options,
+        } => {
+            log::debug!("pattern={pattern:?} all={all:?} options={options:?}");
+            let collections = match all {
+                true => {
+                    let current_dir =
+                        env::current_dir().context("Failed to read the current directory")?;
+                    config
+                        .collections
+                        .iter()
+                        .map(|(name, collection)| {
+                            let project = Project::discover(&current_dir, &collection.lang);
+                            (name.clone(), project.sources(&collection.path))
+                        })
+                        .collect()
+                }
+                false => vec![(selected()?.0, sources()?)],
+            };
+            let highlight = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+            let output = grep_snippets(
+                &collections,
+                &pattern,
+                &options,
+                cli.lang.as_deref(),
+                format(OutputFormat::Plain),
+                highlight,
+            )
+            .context("Failed to grep snippets")?;
+            if !output.is_empty() {
+                println!("{}", output);
             }
         }
         Commands::UpdateKey { old_key, new_key } => {
-            dbg!(&old_key, &new_key);
-
-            update_key_in_file(&config.path, old_key, new_key)
+            log::debug!("old_key={old_key:?} new_key={new_key:?}");
+            let change = update_key_in_file(&snippet_path()?, old_key, new_key)
                 .context("Failed to update key in file")?;
+            Journal::record(&journal_path, change)?;
         }
-        Commands::Config { path } => {
-            dbg!(&path);
-            if let Some(path) = path {
-                let mut config = config;
-                config.update_path(path);
-                config.save(&config_path)?;
-                println!("Configuration updated.");
+        Commands::Config { action, path, .. } => {
+            let updated = match action {
+                Some(ConfigCommands::List) => {
+                    println!(
+                        "{}",
+                        list_collections(&config, format(OutputFormat::Table))?
+                    );
+                    None
+                }
+                Some(ConfigCommands::Add {
+                    name,
+                    path,
+                    extension,
+                }) => {
+                    let lang = cli.lang.clone();
+                    let updated = SnipConfig::modify(&config_path, |config| {
+                        config.add_collection(name.clone(), path, lang, extension)
+                    })?;
+                    Some((updated, format!("Collection '{}' added.", name)))
+                }
+                Some(ConfigCommands::Remove { name }) => {
+                    let updated = SnipConfig::modify(&config_path, |config| {
+                        config.remove_collection(&name).map(|_| ())
+                    })?;
+                    Some((updated, format!("Collection '{}' removed.", name)))
+                }
+                Some(ConfigCommands::Use { name }) => {
+                    let updated =
+                        SnipConfig::modify(&config_path, |config| config.use_collection(&name))?;
+                    Some((updated, format!("Collection '{}' is now active.", name)))
+                }
+                None => {
+                    log::debug!("path={path:?}");
+                    let (name, collection) = selected()?;
+                    match path {
+                        Some(path) => {
+                            let updated = SnipConfig::modify(&config_path, |config| {
+                                config.update_path(&name, path)
+                            })?;
+                            Some((updated, "Configuration updated.".to_string()))
+                        }
+                        None => {
+                            // Shows the selected collection, only its path in plain output
+                            match cli.output {
+                                Some(output) if output.is_json() => {
+                                    let record = CollectionRecord::new(
+                                        &name,
+                                        &collection,
+                                        name == config.active,
+                                    );
+                                    println!("{}", render_json(&record, output)?);
+                                }
+                                Some(OutputFormat::Plain) => println!("{}", collection.path),
+                                _ => {
+                                    println!("{} ({}): {}", name, collection.lang, collection.path)
+                                }
+                            }
+                            None
+                        }
+                    }
+                }
+            };
+            // Changes print the resulting collections in JSON mode
+            if let Some((updated, message)) = updated {
+                match cli.output.filter(OutputFormat::is_json) {
+                    Some(output) => println!("{}", list_collections(&updated, output)?),
+                    None => println!("{}", message),
+                }
             }
         }
         Commands::Open { editor } => {
-            open_file_with(&config_path, editor)
-                .context("Failed to open the configuration file")?;
+            open_file_with(&snippet_path()?, editor)
+                .context("Failed to open the Snippet Collection file")?;
+        }
+        Commands::Undo => {
+            let entry = replay_change(&journal_path, Replay::Undo)
+                .context("Failed to undo the last change")?;
+            println!(
+                "Undid #{} {} in {}",
+                entry.id,
+                entry.change.summary(),
+                entry.change.path
+            );
+        }
+        Commands::Redo => {
+            let entry = replay_change(&journal_path, Replay::Redo)
+                .context("Failed to redo the last undone change")?;
+            println!(
+                "Redid #{} {} in {}",
+                entry.id,
+                entry.change.summary(),
+                entry.change.path
+            );
+        }
+        Commands::History { limit } => {
+            let output = show_history(&journal_path, limit, format(OutputFormat::Table))
+                .context("Failed to show the change history")?;
+            println!("{}", output);
         }
     }
 
     Ok(())
 }
+
+/// Splits body arguments holding several lines, e.g. `-- "$(cat snippet.rs)"`, into lines.
+fn split_lines(body: Vec<String>) -> Vec<String> {
+    body.iter()
+        .flat_map(|arg| arg.split('\n'))
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect()
+}
@@ -1,5 +1,35 @@
+pub mod body_input_model;
 pub mod cli_model;
+pub mod collection_model;
+pub mod collection_record_model;
 pub mod commands_model;
+pub mod error_record_model;
+pub mod expand_context_model;
+pub mod expansion_model;
+pub mod format_report_model;
+pub mod fuzzy_match_model;
+pub mod grep_match_model;
+pub mod grep_options_model;
+pub mod history_record_model;
 pub mod identifier_model;
+pub mod insert_position_model;
+pub mod insert_record_model;
+pub mod journal_model;
+pub mod key_order_model;
+pub mod layer_model;
+pub mod layered_snippets_model;
+pub mod lint_finding_model;
+pub mod lint_rule_model;
+pub mod output_format_model;
+pub mod project_model;
+pub mod search_hit_model;
+pub mod search_index_model;
 pub mod snip_config_model;
+pub mod snippet_change_model;
+pub mod snippet_document_model;
 pub mod snippet_model;
+pub mod snippet_record_model;
+pub mod snippet_source_model;
+pub mod snippet_template_model;
+pub mod string_or_vec_model;
+pub mod tui_state_model;
@@ -0,0 +1,54 @@
+use clap::Args;
+use std::path::PathBuf;
+
+/// Where `add` and `edit` read a snippet body from, instead of the arguments after `--`.
+#[derive(Args, Debug, Clone, Default, PartialEq)]
+pub struct BodyInput {
+    /// Reads the body from a file, or from some of its lines, e.g. `src/lib.rs:10-30`
+    #[arg(long, value_parser = BodyFile::parse, conflicts_with_all = ["stdin", "body"])]
+    pub body_file: Option<BodyFile>,
+    /// Reads the body from stdin
+    #[arg(long, conflicts_with = "body")]
+    pub stdin: bool,
+    /// Removes the indentation shared by all lines of the body read
+    #[arg(long)]
+    pub dedent: bool,
+    /// Keeps `$`, `}` and `\` of the body read as snippet syntax instead of escaping them
+    #[arg(long)]
+    pub raw: bool,
+}
+
+/// `path` or `path:start-end`, lines starting at 1 and the end included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyFile {
+    pub path: PathBuf,
+    pub lines: Option<(usize, usize)>,
+}
+
+impl BodyFile {
+    pub fn parse(spec: &str) -> Result<BodyFile, String> {
+        let range = spec.rsplit_once(':').and_then(|(path, range)| {
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            Some((
+                path,
+                start.parse::<usize>().ok()?,
+                end.parse::<usize>().ok()?,
+            ))
+        });
+        match range {
+            Some((_, start, end)) if start == 0 || end < start => Err(format!(
+                "invalid line range {}-{}, lines start at 1",
+                start, end
+            )),
+            Some((path, start, end)) => Ok(BodyFile {
+                path: PathBuf::from(path),
+                lines: Some((start, end)),
+            }),
+            // No range, the colon may belong to the path, e.g. `C:\snippet.rs`
+            None => Ok(BodyFile {
+                path: PathBuf::from(spec),
+                lines: None,
+            }),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::models::commands_model::Commands;
-use clap::Parser;
+use crate::models::output_format_model::OutputFormat;
+use clap::{ArgAction, Parser};
 
 #[derive(Parser)]
 #[clap(name = "snip")]
@@ -8,4 +9,19 @@ use clap::Parser;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Name of the Snippet Collection to use instead of the active one
+    #[arg(long, global = true)]
+    pub collection: Option<String>,
+    /// Uses the first Snippet Collection configured for this language
+    #[arg(long, global = true)]
+    pub lang: Option<String>,
+    /// Output format of read commands, `json` and `jsonl` also report errors as JSON
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<OutputFormat>,
+    /// Logs more to stderr, repeat for more detail (-v, -vv, -vvv)
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+    /// Only logs errors, overrides the `SNIP_LOG` filter
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
 }
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A named Snippet Collection file for one language.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Collection {
+    /// Language id, e.g. `rust` or `typescript`
+    pub lang: String,
+    pub path: String,
+    /// File extensions of the language, e.g. `rs`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+}
+
+impl Collection {
+    /// A collection whose language is guessed from the file name, e.g. `rust.json` is `rust`.
+    pub fn from_path(path: String) -> Collection {
+        Collection {
+            lang: lang_from_path(&path),
+            path,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// File extension of the language, the first configured one or a well-known one.
+    pub fn extension(&self) -> &str {
+        if let Some(extension) = self.extensions.first() {
+            return extension.trim_start_matches('.');
+        }
+        match self.lang.as_str() {
+            "rust" => "rs",
+            "python" => "py",
+            "javascript" => "js",
+            "typescript" => "ts",
+            "typescriptreact" => "tsx",
+            "javascriptreact" => "jsx",
+            "ruby" => "rb",
+            "shellscript" => "sh",
+            "markdown" => "md",
+            "csharp" => "cs",
+            "cpp" => "cpp",
+            "go" | "lua" | "c" | "java" | "php" | "html" | "css" | "toml" | "yaml" | "json"
+            | "sql" | "swift" | "kt" => &self.lang,
+            _ => "txt",
+        }
+    }
+}
+
+pub fn lang_from_path(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path_guesses_lang() {
+        let collection = Collection::from_path("/snippets/rust.json".to_string());
+        assert_eq!(collection.lang, "rust");
+        assert_eq!(
+            Collection::from_path("/snippets/python.code-snippets".to_string()).lang,
+            "python"
+        );
+        assert_eq!(collection.extension(), "rs");
+    }
+}
@@ -0,0 +1,24 @@
+use crate::models::collection_model::Collection;
+use serde::Serialize;
+
+/// A configured collection as printed by `--output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CollectionRecord {
+    pub name: String,
+    pub lang: String,
+    pub path: String,
+    pub extensions: Vec<String>,
+    pub active: bool,
+}
+
+impl CollectionRecord {
+    pub fn new(name: &str, collection: &Collection, active: bool) -> CollectionRecord {
+        CollectionRecord {
+            name: name.to_string(),
+            lang: collection.lang.clone(),
+            path: collection.path.clone(),
+            extensions: collection.extensions.clone(),
+            active,
+        }
+    }
+}
@@ -1,16 +1,35 @@
+use crate::models::body_input_model::BodyInput;
+use crate::models::grep_options_model::GrepOptions;
 use crate::models::identifier_model::Identifier;
+use crate::models::key_order_model::KeyOrder;
+use crate::models::layer_model::Layer;
+use crate::models::lint_rule_model::LintRule;
 use clap::Subcommand;
+use std::path::PathBuf;
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Adds entry to Snippet Collection file
     Add {
+        #[arg(short, long, required_unless_present = "interactive")]
+        key: Option<String>,
+        /// Repeat to give the snippet several prefixes
+        #[arg(short, long, required_unless_present = "interactive")]
+        prefix: Vec<String>,
+        #[arg(short, long, required_unless_present = "interactive")]
+        description: Option<String>,
+        /// Writes the snippet in $VISUAL or $EDITOR, starting from the fields given
         #[arg(short, long)]
-        key: String,
-        #[arg(short, long)]
-        prefix: String,
-        #[arg(short, long)]
-        description: String,
+        interactive: bool,
+        /// Comma-separated languages the snippet is offered for, e.g. `rust,toml`
+        #[arg(long)]
+        scope: Option<String>,
+        /// Layer to add the snippet to, `user` by default
+        #[arg(long, value_enum)]
+        layer: Option<Layer>,
+        #[command(flatten)]
+        body_input: BodyInput,
+        /// Body lines, an argument holding several lines is split
         #[arg(last(true))]
         body: Vec<String>,
     },
@@ -18,12 +37,28 @@ pub enum Commands {
     Rm { key: String },
     /// Edits entry in Snippet Collection file
     Edit {
+        #[arg(short, long, required_unless_present = "name")]
+        key: Option<String>,
+        /// Key of the snippet, instead of `--key`
+        #[arg(conflicts_with = "key", value_name = "KEY")]
+        name: Option<String>,
+        /// Edits the whole snippet in $VISUAL or $EDITOR
+        #[arg(short, long, conflicts_with_all = ["prefix", "description", "scope", "body", "body_file", "stdin"])]
+        interactive: bool,
+        /// Repeat to give the snippet several prefixes
         #[arg(short, long)]
-        key: String,
-        #[arg(short, long)]
-        prefix: Option<String>,
+        prefix: Option<Vec<String>>,
         #[arg(short, long)]
         description: Option<String>,
+        /// Comma-separated languages the snippet is offered for, empty to offer it everywhere
+        #[arg(long)]
+        scope: Option<String>,
+        /// Layer to write the edit to, by default the one the snippet comes from
+        #[arg(long, value_enum)]
+        layer: Option<Layer>,
+        #[command(flatten)]
+        body_input: BodyInput,
+        /// Body lines, an argument holding several lines is split
         #[arg(last(true))]
         body: Option<Vec<String>>,
     },
@@ -36,13 +71,108 @@ pub enum Commands {
     Show { key: String },
     /// Searches for entries in Snippet Collection file
     Search {
+        /// Fuzzy matches keys or prefixes only, instead of running a query
         #[arg(value_enum)]
         id: Option<Identifier>,
+        /// Ranks snippets by the words of the search term in their key, prefixes,
+        /// description and body
+        #[arg(long, conflicts_with = "id")]
+        full_text: bool,
+        /// Search term, or without an ID a query such as `prefix:/^impl/ body:Deref -tag:old`
         #[arg(last(true))]
         name: String,
     },
-    /// Configures the Snippet Collection file
-    Config { path: Option<String> },
+    /// Searches entry bodies for a regex, printing each matching line
+    Grep {
+        /// Regex to look for in each line of the bodies
+        pattern: String,
+        /// Searches every configured collection instead of the selected one
+        #[arg(long)]
+        all: bool,
+        #[command(flatten)]
+        options: GrepOptions,
+    },
+    /// Renders an entry to its final text, with tabstop defaults and resolved variables
+    Expand {
+        /// Key or prefix of the snippet
+        name: String,
+        /// Value of a tabstop, placeholder or variable, e.g. `--set 1=Foo --set name=bar`
+        #[arg(long, value_parser = parse_key_value)]
+        set: Vec<(String, String)>,
+        /// File the snippet is expanded into, for the `TM_FILE*` variables
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Asks for the value of each tabstop, showing its default and choices
+        #[arg(short, long)]
+        interactive: bool,
+        /// Copies the result to the clipboard instead of printing it
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Renders an entry and splices it into a file, indented like the insertion point
+    Insert {
+        /// Key or prefix of the snippet
+        name: String,
+        /// File to insert the snippet into
+        #[arg(long)]
+        file: PathBuf,
+        /// Line the snippet starts on, starting at 1
+        #[arg(long, required_unless_present = "after_pattern")]
+        line: Option<usize>,
+        /// Inserts after the first line matching this regex
+        #[arg(long, conflicts_with = "line")]
+        after_pattern: Option<String>,
+        /// Value of a tabstop, placeholder or variable, e.g. `--set 1=Foo --set name=bar`
+        #[arg(long, value_parser = parse_key_value)]
+        set: Vec<(String, String)>,
+        /// Asks for the value of each tabstop, showing its default and choices
+        #[arg(short, long)]
+        interactive: bool,
+    },
+    /// Checks Snippet Collection files, fails when a denied rule finds problems
+    Lint {
+        /// Files to check, by default the selected collection and its project files
+        paths: Vec<String>,
+        /// Fixes the problems that can be fixed without changing what the snippets produce
+        #[arg(long)]
+        fix: bool,
+        /// Rules to skip, e.g. `--allow empty-description,unknown-variable`
+        #[arg(long, value_enum, value_delimiter = ',')]
+        allow: Vec<LintRule>,
+        /// Rules reported without failing
+        #[arg(long, value_enum, value_delimiter = ',')]
+        warn: Vec<LintRule>,
+        /// Rules that make the command fail
+        #[arg(long, value_enum, value_delimiter = ',')]
+        deny: Vec<LintRule>,
+    },
+    /// Rewrites Snippet Collection files in a canonical form
+    Fmt {
+        /// Files to format, by default the selected collection and its project files
+        paths: Vec<String>,
+        /// Lists the files that aren't formatted and fails, without changing them
+        #[arg(long)]
+        check: bool,
+        /// Order of the snippets, by default the `key_order` of the config
+        #[arg(long, value_enum)]
+        key_order: Option<KeyOrder>,
+    },
+    /// Browses, previews and manages entries in a full-screen terminal UI
+    Tui,
+    /// Configures the Snippet Collection files
+    #[command(args_conflicts_with_subcommands = true)]
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommands>,
+        /// Sets the path of the selected Snippet Collection
+        path: Option<String>,
+        /// Rewrites a config that fails to load, keeping its snippet path when it can be read
+        #[arg(long, conflicts_with_all = ["path", "reset"])]
+        repair: bool,
+        /// Replaces the config with the defaults
+        #[arg(long, conflicts_with = "path")]
+        reset: bool,
+    },
     UpdateKey {
         #[arg(short, long)]
         old_key: String,
@@ -53,4 +183,38 @@ pub enum Commands {
         #[arg(short, long)]
         editor: Option<String>,
     },
+    /// Reverts the last change made to a Snippet Collection file
+    Undo,
+    /// Applies the last undone change again
+    Redo,
+    /// Lists the changes made to Snippet Collection files
+    History {
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Lists the configured Snippet Collections
+    List,
+    /// Adds a Snippet Collection, its language is taken from `--lang` or the file name
+    Add {
+        name: String,
+        path: String,
+        /// File extensions of the language, e.g. `-e rs`
+        #[arg(short, long)]
+        extension: Vec<String>,
+    },
+    /// Removes a Snippet Collection from the config, its file is kept
+    Remove { name: String },
+    /// Makes a Snippet Collection the active one
+    Use { name: String },
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected `key=value`, got `{}`", value))
 }
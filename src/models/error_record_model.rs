@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+/// An error as printed to stderr by `--output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ErrorRecord {
+    pub error: String,
+    /// The underlying causes, outermost first
+    pub causes: Vec<String>,
+}
+
+impl ErrorRecord {
+    pub fn new(error: &anyhow::Error) -> ErrorRecord {
+        ErrorRecord {
+            error: error.to_string(),
+            causes: error.chain().skip(1).map(ToString::to_string).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_error_record_lists_causes() {
+        let error = Err::<(), _>(anyhow::anyhow!("Snippet file not found or is empty"))
+            .context("Failed to list snippets from file")
+            .unwrap_err();
+
+        assert_eq!(
+            ErrorRecord::new(&error),
+            ErrorRecord {
+                error: "Failed to list snippets from file".to_string(),
+                causes: vec!["Snippet file not found or is empty".to_string()],
+            }
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// What `snip expand` knows about the place a snippet is expanded into.
+#[derive(Debug, Clone)]
+pub struct ExpandContext {
+    /// Values given with `--set`, keyed by tabstop index, placeholder default or variable name
+    pub values: HashMap<String, String>,
+    /// The file the snippet is expanded into, for the `TM_FILE*` variables
+    pub file: Option<PathBuf>,
+    /// The folder of the workspace, for `WORKSPACE_*` and `RELATIVE_FILEPATH`
+    pub workspace: Option<PathBuf>,
+    /// Language id, for the comment variables
+    pub lang: Option<String>,
+    pub now: DateTime<Local>,
+}
+
+impl Default for ExpandContext {
+    fn default() -> ExpandContext {
+        ExpandContext {
+            values: HashMap::new(),
+            file: None,
+            workspace: None,
+            lang: None,
+            now: Local::now(),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::helpers::jsonc::line_col;
+use serde::Serialize;
+
+/// The text a snippet expands to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub text: String,
+    /// Byte offset of `$0` in `text`, the end of the text when the snippet has none
+    pub cursor: usize,
+}
+
+/// An expansion as printed by `snip expand --output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExpansionRecord {
+    pub key: String,
+    pub text: String,
+    pub cursor: CursorRecord,
+}
+
+/// Final cursor position, `line` and `column` (in characters) start at 1.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CursorRecord {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ExpansionRecord {
+    pub fn new(key: &str, expansion: Expansion) -> ExpansionRecord {
+        let (line, column) = line_col(&expansion.text, expansion.cursor);
+        ExpansionRecord {
+            key: key.to_string(),
+            cursor: CursorRecord {
+                offset: expansion.cursor,
+                line,
+                column,
+            },
+            text: expansion.text,
+        }
+    }
+}
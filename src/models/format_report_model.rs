@@ -0,0 +1,9 @@
+use crate::models::snippet_change_model::SnippetChange;
+
+/// The files `snip fmt` found unformatted, and the rewrites made without `--check`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatReport {
+    /// The paths as given
+    pub unformatted: Vec<String>,
+    pub changes: Vec<SnippetChange>,
+}
@@ -0,0 +1,8 @@
+/// How well a pattern matches a text, see `fuzzy_match`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FuzzyMatch {
+    /// Higher is better, only comparable between matches of the same pattern
+    pub score: i32,
+    /// Char indices of the text matched by each char of the pattern
+    pub positions: Vec<usize>,
+}
@@ -0,0 +1,30 @@
+use crate::models::layer_model::Layer;
+use serde::Serialize;
+
+/// A body line matched by `snip grep`, as printed by `--output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GrepMatch {
+    pub collection: String,
+    pub key: String,
+    pub layer: Layer,
+    /// Path of the file the snippet comes from
+    pub source: String,
+    /// Line of the body, starting at 1
+    pub line: usize,
+    pub text: String,
+    /// Byte ranges of the matches in `text`
+    pub ranges: Vec<(usize, usize)>,
+    /// Context lines asked for with `-B` and `-A`
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// A snippet with matches, as printed by `snip grep --count` or `--keys-only` with
+/// `--output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GrepCount {
+    pub collection: String,
+    pub key: String,
+    /// Number of matching lines
+    pub count: usize,
+}
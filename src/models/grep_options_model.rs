@@ -0,0 +1,37 @@
+use clap::Args;
+
+/// How `snip grep` matches and what it prints.
+#[derive(Args, Debug, Clone, Default, PartialEq)]
+pub struct GrepOptions {
+    /// Ignores case
+    #[arg(short, long)]
+    pub ignore_case: bool,
+    /// Only matches whole words
+    #[arg(short, long)]
+    pub word_regexp: bool,
+    /// Prints only the keys of the snippets with a match
+    #[arg(short = 'l', long, conflicts_with = "count")]
+    pub keys_only: bool,
+    /// Prints the number of matching lines of each snippet with a match
+    #[arg(short, long)]
+    pub count: bool,
+    /// Lines to print after each match
+    #[arg(short = 'A', long, value_name = "NUM", default_value_t = 0)]
+    pub after_context: usize,
+    /// Lines to print before each match
+    #[arg(short = 'B', long, value_name = "NUM", default_value_t = 0)]
+    pub before_context: usize,
+    /// Lines to print before and after each match
+    #[arg(short = 'C', long, value_name = "NUM")]
+    pub context: Option<usize>,
+}
+
+impl GrepOptions {
+    pub fn before(&self) -> usize {
+        self.context.unwrap_or(self.before_context)
+    }
+
+    pub fn after(&self) -> usize {
+        self.context.unwrap_or(self.after_context)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::models::journal_model::JournalEntry;
+use crate::models::snippet_change_model::Operation;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A journal entry as printed by `snip history --output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub operation: Operation,
+    pub key: String,
+    pub path: String,
+    /// `applied` or `undone`
+    pub state: &'static str,
+}
+
+impl HistoryRecord {
+    pub fn new(entry: &JournalEntry, applied: bool) -> HistoryRecord {
+        HistoryRecord {
+            id: entry.id,
+            timestamp: entry.timestamp,
+            operation: entry.change.operation,
+            key: entry.change.key().to_string(),
+            path: entry.change.path.clone(),
+            state: if applied { "applied" } else { "undone" },
+        }
+    }
+}
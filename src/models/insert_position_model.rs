@@ -0,0 +1,8 @@
+/// Where `snip insert` splices a snippet into a file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertPosition {
+    /// Before the line with this number, starting at 1, so the snippet starts on that line
+    Line(usize),
+    /// After the first line matching this regex
+    AfterPattern(String),
+}
@@ -0,0 +1,15 @@
+use crate::models::expansion_model::CursorRecord;
+use serde::Serialize;
+
+/// An insertion as printed by `snip insert --output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct InsertRecord {
+    pub key: String,
+    pub path: String,
+    /// First line of the inserted text, starting at 1
+    pub line: usize,
+    /// Number of inserted lines
+    pub lines: usize,
+    /// Where `$0` is in the updated file
+    pub cursor: CursorRecord,
+}
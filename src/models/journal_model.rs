@@ -0,0 +1,144 @@
+use crate::constants::{JOURNAL_FILE_NAME, MAX_JOURNAL_ENTRIES};
+use crate::helpers::atomic_write::atomic_write;
+use crate::helpers::file_lock::FileLock;
+use crate::models::snippet_change_model::SnippetChange;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub change: SnippetChange,
+}
+
+/// The history of changes made through `snip`, stored next to the config file.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+    /// Number of entries currently applied, the entries after it have been undone
+    pub applied: usize,
+}
+
+impl Journal {
+    pub fn path_for(config_path: &str) -> PathBuf {
+        Path::new(config_path).with_file_name(JOURNAL_FILE_NAME)
+    }
+
+    /// Loads the journal, starting an empty one when the file doesn't exist yet.
+    pub fn load(path: &Path) -> anyhow::Result<Journal> {
+        if !path.exists() || path.metadata()?.len() == 0 {
+            return Ok(Journal::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read journal file {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse journal file {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize journal")?;
+        atomic_write(path, &content).context("Failed to write journal file")
+    }
+
+    /// Adds a change to the journal file while holding its lock.
+    pub fn record(path: &Path, change: SnippetChange) -> anyhow::Result<JournalEntry> {
+        let _lock = FileLock::acquire(path)?;
+        let mut journal = Journal::load(path)?;
+        let entry = journal.push(change).clone();
+        journal.write(path)?;
+        log::info!(
+            "Recorded change #{}: {} in {}",
+            entry.id,
+            entry.change.summary(),
+            entry.change.path
+        );
+        Ok(entry)
+    }
+
+    /// Adds a change on top of the applied entries, dropping the ones that were undone.
+    pub fn push(&mut self, change: SnippetChange) -> &JournalEntry {
+        self.entries.truncate(self.applied);
+        let id = self.entries.last().map_or(1, |entry| entry.id + 1);
+        self.entries.push(JournalEntry {
+            id,
+            timestamp: Utc::now(),
+            change,
+        });
+        if self.entries.len() > MAX_JOURNAL_ENTRIES {
+            self.entries
+                .drain(..self.entries.len() - MAX_JOURNAL_ENTRIES);
+        }
+        self.applied = self.entries.len();
+        self.entries.last().expect("an entry was just pushed")
+    }
+
+    /// The entry `snip undo` would revert.
+    pub fn undo_index(&self) -> Option<usize> {
+        self.applied.checked_sub(1)
+    }
+
+    /// The entry `snip redo` would apply again.
+    pub fn redo_index(&self) -> Option<usize> {
+        (self.applied < self.entries.len()).then_some(self.applied)
+    }
+
+    /// Records that the file of entry `index` now hashes to `hash` after it was undone, so the
+    /// neighbouring entries on the same file expect that state.
+    pub fn mark_undone(&mut self, index: usize, hash: String) {
+        let path = self.entries[index].change.path.clone();
+        if let Some(previous) = self.entries[..index]
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.change.path == path)
+        {
+            previous.change.after_hash = hash.clone();
+        }
+        self.entries[index].change.before_hash = hash;
+        self.applied = index;
+    }
+
+    /// Records that the file of entry `index` now hashes to `hash` after it was redone.
+    pub fn mark_redone(&mut self, index: usize, hash: String) {
+        let path = self.entries[index].change.path.clone();
+        if let Some(next) = self.entries[index + 1..]
+            .iter_mut()
+            .find(|entry| entry.change.path == path)
+        {
+            next.change.before_hash = hash.clone();
+        }
+        self.entries[index].change.after_hash = hash;
+        self.applied = index + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::snippet_change_model::Operation;
+
+    fn change(key: &str) -> SnippetChange {
+        SnippetChange::new("rust.json", Operation::Remove, None, None, key, "")
+    }
+
+    #[test]
+    fn test_push_drops_undone_entries() {
+        let mut journal = Journal::default();
+        journal.push(change("a"));
+        journal.push(change("b"));
+        journal.mark_undone(1, "hash".to_string());
+
+        assert_eq!(journal.entries[0].change.after_hash, "hash");
+        assert_eq!(journal.redo_index(), Some(1));
+
+        journal.push(change("c"));
+        assert_eq!(journal.entries.len(), 2);
+        assert_eq!(journal.entries[1].id, 2);
+        assert_eq!(journal.redo_index(), None);
+        assert_eq!(journal.undo_index(), Some(1));
+    }
+}
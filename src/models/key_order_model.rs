@@ -0,0 +1,21 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Order of the snippets in a collection file written by `snip fmt`.
+#[derive(
+    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, ValueEnum, Serialize, Deserialize, Debug,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyOrder {
+    /// Sorted by key
+    #[default]
+    Sorted,
+    /// In the order the snippets were added
+    Insertion,
+}
+
+impl KeyOrder {
+    pub fn is_default(&self) -> bool {
+        *self == KeyOrder::default()
+    }
+}
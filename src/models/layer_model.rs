@@ -0,0 +1,26 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt;
+
+/// Where a snippet comes from. Project snippets take precedence over user ones.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    Project,
+    User,
+}
+
+impl Layer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Layer::Project => "project",
+            Layer::User => "user",
+        }
+    }
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
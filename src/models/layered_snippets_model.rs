@@ -0,0 +1,119 @@
+use crate::models::snippet_document_model::SnippetDocument;
+use crate::models::snippet_model::Snippet;
+use crate::models::snippet_source_model::SnippetSource;
+use anyhow::Context;
+use std::fs;
+use std::path::Path;
+
+/// The snippets of several files, where a key in an earlier source hides the same key in
+/// later ones.
+#[derive(Debug, Default)]
+pub struct LayeredSnippets {
+    layers: Vec<(SnippetSource, SnippetDocument)>,
+}
+
+impl LayeredSnippets {
+    /// Reads the sources in order of precedence. Missing files are skipped, but at least one
+    /// of them must hold snippets.
+    pub fn load(sources: &[SnippetSource]) -> anyhow::Result<LayeredSnippets> {
+        let mut layers = Vec::new();
+        for source in sources {
+            let path = Path::new(&source.path);
+            if !path.exists() || path.metadata()?.len() == 0 {
+                continue;
+            }
+            let file_contents = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read the snippets file {}", source.path))?;
+            let document = SnippetDocument::parse(file_contents)
+                .with_context(|| format!("Failed to parse the snippets file {}", source.path))?;
+            layers.push((source.clone(), document));
+        }
+        if layers.is_empty() {
+            return Err(anyhow::anyhow!("Snippet file not found or is empty"));
+        }
+        Ok(LayeredSnippets { layers })
+    }
+
+    /// Snippets that are not hidden by a source with higher precedence, in source then file
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Snippet, &SnippetSource)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, (source, document))| {
+                document
+                    .iter()
+                    .filter(move |(key, _)| {
+                        !self.layers[..index]
+                            .iter()
+                            .any(|(_, higher)| higher.contains_key(key))
+                    })
+                    .map(move |(key, snippet)| (key, snippet, source))
+            })
+    }
+
+    /// The visible snippet with the key `name`, else the first one with the prefix `name`.
+    pub fn find(&self, name: &str) -> Option<(&str, &Snippet, &SnippetSource)> {
+        self.get_key_value(name).or_else(|| {
+            self.iter()
+                .find(|(_, snippet, _)| snippet.prefix.contains(name))
+        })
+    }
+
+    fn get_key_value(&self, key: &str) -> Option<(&str, &Snippet, &SnippetSource)> {
+        self.iter().find(|(k, _, _)| *k == key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<(&Snippet, &SnippetSource)> {
+        self.layers
+            .iter()
+            .find_map(|(source, document)| document.get(key).map(|snippet| (snippet, source)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::layer_model::Layer;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_project_layer_hides_user_snippets() {
+        let temp_dir = tempdir().unwrap();
+        let project = temp_dir.path().join("team.code-snippets");
+        let user = temp_dir.path().join("rust.json");
+        fs::write(
+            &project,
+            r#"{ "a": { "prefix": "pa", "body": "project" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            &user,
+            r#"{ "a": { "prefix": "ua", "body": "user" }, "b": { "prefix": "b", "body": "" } }"#,
+        )
+        .unwrap();
+        let sources = vec![
+            SnippetSource::new(Layer::Project, project.to_str().unwrap()),
+            SnippetSource::user(user.to_str().unwrap()),
+            SnippetSource::new(
+                Layer::Project,
+                temp_dir.path().join("missing.json").to_str().unwrap(),
+            ),
+        ];
+
+        let snippets = LayeredSnippets::load(&sources).unwrap();
+
+        let entries: Vec<_> = snippets
+            .iter()
+            .map(|(key, snippet, source)| (key, snippet.body_text(), source.layer))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("a", "project".to_string(), Layer::Project),
+                ("b", String::new(), Layer::User)
+            ]
+        );
+        assert_eq!(snippets.get("a").unwrap().1.layer, Layer::Project);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::models::lint_rule_model::{LintLevel, LintRule};
+use crate::models::snippet_change_model::SnippetChange;
+use serde::Serialize;
+
+/// A problem found by `snip lint`, also the record printed with `--output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub level: LintLevel,
+    pub path: String,
+    pub key: String,
+    /// Position in the collection file, starting at 1
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// Whether `--fix` can fix it
+    pub fixable: bool,
+}
+
+/// The findings left after linting, and the fixes applied with `--fix`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+    pub changes: Vec<SnippetChange>,
+}
+
+impl LintReport {
+    /// Findings of denied rules, which make `snip lint` fail.
+    pub fn errors(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.level == LintLevel::Deny)
+            .count()
+    }
+}
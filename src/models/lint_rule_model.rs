@@ -0,0 +1,112 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A check run by `snip lint`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum, Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// A key defined several times, serde keeps the last one
+    DuplicateKey,
+    /// A prefix used twice, or by two snippets differing only in case
+    DuplicatePrefix,
+    EmptyBody,
+    EmptyDescription,
+    /// Tabstops not numbered `1, 2, 3, ...`
+    TabstopGap,
+    /// Mirrors of a tabstop with different defaults
+    ConflictingDefaults,
+    /// A literal `$` or `}` in text that should be escaped
+    UnescapedCharacter,
+    UnknownVariable,
+    TrailingWhitespace,
+}
+
+/// What happens with the findings of a rule: `deny` makes `snip lint` fail.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintRule::DuplicateKey => "duplicate-key",
+            LintRule::DuplicatePrefix => "duplicate-prefix",
+            LintRule::EmptyBody => "empty-body",
+            LintRule::EmptyDescription => "empty-description",
+            LintRule::TabstopGap => "tabstop-gap",
+            LintRule::ConflictingDefaults => "conflicting-defaults",
+            LintRule::UnescapedCharacter => "unescaped-character",
+            LintRule::UnknownVariable => "unknown-variable",
+            LintRule::TrailingWhitespace => "trailing-whitespace",
+        }
+    }
+
+    /// Problems that change what the snippet produces are denied, style problems are warnings.
+    pub fn default_level(&self) -> LintLevel {
+        match self {
+            LintRule::DuplicateKey
+            | LintRule::DuplicatePrefix
+            | LintRule::EmptyBody
+            | LintRule::ConflictingDefaults => LintLevel::Deny,
+            _ => LintLevel::Warn,
+        }
+    }
+
+    /// Whether `snip lint --fix` can fix the rule without changing what the snippet produces.
+    pub fn is_fixable(&self) -> bool {
+        matches!(
+            self,
+            LintRule::TabstopGap | LintRule::UnescapedCharacter | LintRule::TrailingWhitespace
+        )
+    }
+}
+
+impl LintLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The level of each rule, the default one unless overridden with `--allow`, `--warn` or
+/// `--deny`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintLevels {
+    overrides: HashMap<LintRule, LintLevel>,
+}
+
+impl LintLevels {
+    pub fn set(&mut self, rules: &[LintRule], level: LintLevel) {
+        for rule in rules {
+            self.overrides.insert(*rule, level);
+        }
+    }
+
+    pub fn level(&self, rule: LintRule) -> LintLevel {
+        self.overrides
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+}
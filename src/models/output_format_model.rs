@@ -0,0 +1,19 @@
+use clap::ValueEnum;
+
+/// How read commands print their results.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON document
+    Json,
+    /// One compact JSON record per line
+    Jsonl,
+    Table,
+    /// Tab-separated lines
+    Plain,
+}
+
+impl OutputFormat {
+    pub fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Jsonl)
+    }
+}
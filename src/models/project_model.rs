@@ -0,0 +1,162 @@
+use crate::models::layer_model::Layer;
+use crate::models::snippet_source_model::SnippetSource;
+use dirs_next::home_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snippet files shared through a repository, found by walking up from the working directory.
+///
+/// Each directory up to the repository root (the first one holding `.git`) is checked for
+/// `.snip/*.code-snippets`, `.snip/<lang>.json` and `.vscode/*.code-snippets`. Files in
+/// nearer directories come first and take precedence. Outside a repository the walk stops
+/// below the home directory, whose `.vscode` and `.snip` aren't a project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    pub root: PathBuf,
+    pub snippet_files: Vec<PathBuf>,
+}
+
+impl Project {
+    pub fn discover(start: &Path, lang: &str) -> Project {
+        Project::discover_below(start, lang, home_dir().as_deref())
+    }
+
+    fn discover_below(start: &Path, lang: &str, home: Option<&Path>) -> Project {
+        let mut snippet_files = Vec::new();
+        let mut root = start.to_path_buf();
+        for dir in start.ancestors().take_while(|dir| Some(*dir) != home) {
+            snippet_files.extend(snippet_files_in(&dir.join(".snip"), lang));
+            snippet_files.extend(snippet_files_in(&dir.join(".vscode"), lang));
+            if dir.join(".git").exists() {
+                root = dir.to_path_buf();
+                break;
+            }
+        }
+        log::debug!(
+            "Project root {} with snippet files {:?}",
+            root.display(),
+            snippet_files
+        );
+        Project {
+            root,
+            snippet_files,
+        }
+    }
+
+    /// The project snippet files followed by the user collection, in order of precedence.
+    pub fn sources(&self, user_path: &str) -> Vec<SnippetSource> {
+        self.snippet_files
+            .iter()
+            .map(|path| SnippetSource::new(Layer::Project, path.to_string_lossy()))
+            .chain([SnippetSource::user(user_path)])
+            .collect()
+    }
+
+    /// The file project snippets are added to: the nearest project snippet file, or a new
+    /// `.snip/<lang>.json` at the repository root.
+    pub fn write_target(&self, lang: &str) -> PathBuf {
+        self.snippet_files
+            .first()
+            .cloned()
+            .unwrap_or_else(|| self.root.join(".snip").join(format!("{}.json", lang)))
+    }
+
+    /// The file of `layer` snippets are written to, `user_path` for the user layer.
+    pub fn layer_path(&self, user_path: &str, lang: &str, layer: Layer) -> String {
+        match layer {
+            Layer::User => user_path.to_string(),
+            Layer::Project => self.write_target(lang).to_string_lossy().into_owned(),
+        }
+    }
+}
+
+fn snippet_files_in(dir: &Path, lang: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let is_vscode = dir.file_name().is_some_and(|name| name == ".vscode");
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| match path.extension().and_then(|ext| ext.to_str()) {
+            Some("code-snippets") => true,
+            Some("json") => !is_vscode && path.file_stem().is_some_and(|stem| stem == lang),
+            _ => false,
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_discover_walks_up_to_repository_root() {
+        let temp_dir = tempdir().unwrap();
+        let repo = temp_dir.path().join("repo");
+        let crate_dir = repo.join("crates").join("app");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join(".vscode")).unwrap();
+        fs::create_dir_all(crate_dir.join(".snip")).unwrap();
+        fs::create_dir_all(temp_dir.path().join(".snip")).unwrap();
+        fs::write(repo.join(".vscode/team.code-snippets"), "{}").unwrap();
+        fs::write(repo.join(".vscode/settings.json"), "{}").unwrap();
+        fs::write(crate_dir.join(".snip/rust.json"), "{}").unwrap();
+        fs::write(crate_dir.join(".snip/python.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join(".snip/rust.json"), "{}").unwrap();
+
+        let project = Project::discover(&crate_dir, "rust");
+
+        assert_eq!(project.root, repo);
+        assert_eq!(
+            project.snippet_files,
+            vec![
+                crate_dir.join(".snip/rust.json"),
+                repo.join(".vscode/team.code-snippets")
+            ]
+        );
+        assert_eq!(
+            project.write_target("rust"),
+            crate_dir.join(".snip/rust.json")
+        );
+    }
+
+    #[test]
+    fn test_discover_stops_below_home_outside_repository() {
+        let temp_dir = tempdir().unwrap();
+        let home = temp_dir.path().join("home");
+        let project_dir = home.join("work").join("app");
+        fs::create_dir_all(home.join(".vscode")).unwrap();
+        fs::create_dir_all(project_dir.join(".snip")).unwrap();
+        fs::write(home.join(".vscode/global.code-snippets"), "{}").unwrap();
+        fs::write(project_dir.join(".snip/rust.json"), "{}").unwrap();
+
+        let project = Project::discover_below(&project_dir, "rust", Some(&home));
+
+        assert_eq!(project.root, project_dir);
+        assert_eq!(
+            project.snippet_files,
+            vec![project_dir.join(".snip/rust.json")]
+        );
+    }
+
+    #[test]
+    fn test_write_target_without_snippet_files() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+
+        let project = Project::discover(temp_dir.path(), "rust");
+
+        assert_eq!(
+            project.write_target("rust"),
+            temp_dir.path().join(".snip/rust.json")
+        );
+        assert_eq!(
+            project.layer_path("user.json", "rust", Layer::User),
+            "user.json"
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use crate::models::snippet_record_model::SnippetRecord;
+use serde::Serialize;
+
+/// A snippet found by `snip search --full-text`, as printed by `--output json|jsonl`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub record: SnippetRecord,
+    /// BM25 score, higher is better
+    pub score: f64,
+    /// Body line with the most search terms, starting at 1
+    pub line: Option<usize>,
+    /// That line, trimmed
+    pub context: Option<String>,
+}
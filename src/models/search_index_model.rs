@@ -0,0 +1,110 @@
+use crate::helpers::tokenize_code::tokenize_code;
+use crate::models::snippet_model::Snippet;
+use std::collections::HashMap;
+
+// Usual BM25 parameters: how fast repeated terms saturate, how much length normalizes
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+/// A term in the key or a prefix tells more about a snippet than one in its body
+const KEY_WEIGHT: f64 = 3.0;
+const PREFIX_WEIGHT: f64 = 3.0;
+const DESCRIPTION_WEIGHT: f64 = 2.0;
+const BODY_WEIGHT: f64 = 1.0;
+
+/// An in-memory full-text index of snippets, ranking them with BM25 over the terms of
+/// `tokenize_code`.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Weighted term frequencies of each document
+    documents: Vec<HashMap<String, f64>>,
+    /// Number of documents each term is in
+    document_frequency: HashMap<String, usize>,
+}
+
+impl SearchIndex {
+    /// Indexes the key, prefixes, description and body of a snippet, returning its document
+    /// id, numbered from 0 in the order added.
+    pub fn add(&mut self, key: &str, snippet: &Snippet) -> usize {
+        let mut frequencies: HashMap<String, f64> = HashMap::new();
+        let fields = [
+            (key.to_string(), KEY_WEIGHT),
+            (snippet.prefix.join(" "), PREFIX_WEIGHT),
+            (snippet.description().to_string(), DESCRIPTION_WEIGHT),
+            (snippet.body_text(), BODY_WEIGHT),
+        ];
+        for (text, weight) in fields {
+            for term in tokenize_code(&text) {
+                *frequencies.entry(term).or_default() += weight;
+            }
+        }
+        for term in frequencies.keys() {
+            *self.document_frequency.entry(term.clone()).or_default() += 1;
+        }
+        self.documents.push(frequencies);
+        self.documents.len() - 1
+    }
+
+    /// Documents with any term of `query`, best first, with their BM25 score.
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        let mut terms = tokenize_code(query);
+        terms.sort();
+        terms.dedup();
+
+        let count = self.documents.len() as f64;
+        let length = |document: &HashMap<String, f64>| document.values().sum::<f64>();
+        let average_length = self.documents.iter().map(length).sum::<f64>() / count.max(1.0);
+        let mut hits: Vec<(usize, f64)> = self
+            .documents
+            .iter()
+            .enumerate()
+            .filter_map(|(id, document)| {
+                let normalization = 1.0 - B + B * length(document) / average_length;
+                let score: f64 = terms
+                    .iter()
+                    .filter_map(|term| {
+                        let frequency = *document.get(term)?;
+                        let containing = self.document_frequency[term] as f64;
+                        let idf = ((count - containing + 0.5) / (containing + 0.5) + 1.0).ln();
+                        Some(idf * frequency * (K1 + 1.0) / (frequency + K1 * normalization))
+                    })
+                    .sum();
+                (score > 0.0).then_some((id, score))
+            })
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(body: &str, description: &str) -> Snippet {
+        Snippet {
+            prefix: "p".into(),
+            body: vec![body.to_string()].into(),
+            description: Some(description.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_index_ranking() {
+        let mut index = SearchIndex::default();
+        index.add("deref", &snippet("impl Deref for $1 {}", "Deref"));
+        index.add(
+            "deref_mut",
+            &snippet("impl DerefMut for $1 {}", "Mutable deref"),
+        );
+        index.add("clone", &snippet("impl Clone for $1 {}", "Clone"));
+
+        // The whole compound word only matches `DerefMut`, its parts match `Deref` too
+        let ids: Vec<usize> = index.search("DerefMut").iter().map(|hit| hit.0).collect();
+        assert_eq!(ids, vec![1, 0]);
+        // Terms in every document carry little weight, missing terms none
+        let ids: Vec<usize> = index.search("clone impl").iter().map(|hit| hit.0).collect();
+        assert_eq!(ids[0], 2);
+        assert!(index.search("missing").is_empty());
+    }
+}
@@ -1,30 +1,366 @@
+use crate::constants::DEFAULT_SNIPPET_PATH;
+use crate::helpers::atomic_write::atomic_write;
 use crate::helpers::expand_home_dir::expand_home_dir;
+use crate::helpers::file_lock::FileLock;
+use crate::helpers::jsonc;
+use crate::models::collection_model::{lang_from_path, Collection};
+use crate::models::key_order_model::KeyOrder;
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SnipConfig {
-    pub path: String,
+    /// Name of the collection used when none is selected on the command line
+    #[serde(default)]
+    pub active: String,
+    #[serde(default)]
+    pub collections: BTreeMap<String, Collection>,
+    /// Order `snip fmt` writes the snippets of a collection in
+    #[serde(default, skip_serializing_if = "KeyOrder::is_default")]
+    pub key_order: KeyOrder,
+    /// Single snippet path of configs written before collections existed, migrated on load
+    #[serde(default, rename = "path", skip_serializing)]
+    legacy_path: Option<String>,
 }
 
 impl SnipConfig {
     pub fn load(path: &str) -> anyhow::Result<SnipConfig> {
-        let config_content = fs::read_to_string(path).context("Failed to read config file")?;
-        let config: SnipConfig =
-            serde_json::from_str(&config_content).context("Failed to parse config file")?;
-        println!("{}", &config.path);
+        let config_content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path))?;
+        let mut config: SnipConfig = serde_json::from_str(&config_content)
+            .with_context(|| format!("Failed to parse config file {}", path))?;
+        config.migrate();
+        config
+            .validate()
+            .with_context(|| format!("Invalid config file {}", path))?;
         Ok(config)
     }
 
+    /// Loads the config, initializing it with the defaults only when the file is empty,
+    /// i.e. it was just created. A config that fails to load is left untouched.
+    ///
+    /// A config using the old single `path` shape is migrated and saved.
+    pub fn load_or_init(path: &str) -> anyhow::Result<SnipConfig> {
+        let config_content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path))?;
+        if config_content.is_empty() {
+            log::info!("Initializing config file {} with the defaults", path);
+            let config = SnipConfig::default_config();
+            config.save(path)?;
+            return Ok(config);
+        }
+        let config = SnipConfig::load(path)?;
+        if !config_content.contains("\"collections\"") {
+            config.save(path)?;
+        }
+        Ok(config)
+    }
+
+    pub fn default_config() -> SnipConfig {
+        SnipConfig::with_path(
+            expand_home_dir(DEFAULT_SNIPPET_PATH)
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+
+    /// A config holding a single collection, named after its language.
+    fn with_path(path: String) -> SnipConfig {
+        let collection = Collection::from_path(path);
+        SnipConfig {
+            active: collection.lang.clone(),
+            collections: BTreeMap::from([(collection.lang.clone(), collection)]),
+            key_order: KeyOrder::default(),
+            legacy_path: None,
+        }
+    }
+
+    fn migrate(&mut self) {
+        if let Some(path) = self.legacy_path.take() {
+            if self.collections.is_empty() {
+                log::info!("Migrating the single snippet path {} to a collection", path);
+                *self = SnipConfig::with_path(path);
+            }
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if !self.collections.contains_key(&self.active) {
+            return Err(anyhow::anyhow!(
+                "The active collection '{}' is not configured",
+                self.active
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rewrites a config that fails to load, keeping the collections or the snippet path it
+    /// holds when they can still be read. The broken file is kept as a backup next to it.
+    pub fn repair(path: &str) -> anyhow::Result<(SnipConfig, PathBuf)> {
+        let _lock = FileLock::acquire(Path::new(path))?;
+        let config_content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path))?;
+        let value = jsonc::parse(&config_content)
+            .map(|root| root.to_value())
+            .unwrap_or_default();
+
+        let recovered = serde_json::from_value::<SnipConfig>(value.clone())
+            .ok()
+            .map(|mut config| {
+                config.migrate();
+                config
+            })
+            .filter(|config| config.validate().is_ok());
+        let config = match recovered {
+            Some(config) => config,
+            None => {
+                let snippet_path = value["path"].as_str().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Could not find any snippet collection in config file {}, use `snip config --reset` to start over",
+                        path
+                    )
+                })?;
+                SnipConfig::with_path(expand_home_dir(snippet_path).to_string_lossy().into_owned())
+            }
+        };
+
+        let backup = SnipConfig::backup(path)?;
+        config.write(path)?;
+        Ok((config, backup))
+    }
+
+    /// Replaces the config with the defaults, keeping the old file as a backup next to it.
+    pub fn reset(path: &str) -> anyhow::Result<(SnipConfig, PathBuf)> {
+        let _lock = FileLock::acquire(Path::new(path))?;
+        let backup = SnipConfig::backup(path)?;
+        let config = SnipConfig::default_config();
+        config.write(path)?;
+        Ok((config, backup))
+    }
+
+    fn backup(path: &str) -> anyhow::Result<PathBuf> {
+        let backup = PathBuf::from(format!("{}.bak", path));
+        fs::copy(path, &backup)
+            .with_context(|| format!("Failed to back up config file to {}", backup.display()))?;
+        Ok(backup)
+    }
+
     pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        let _lock = FileLock::acquire(Path::new(path))?;
+        self.write(path)
+    }
+
+    /// Loads, changes and saves the config while holding its lock.
+    pub fn modify<F>(path: &str, change: F) -> anyhow::Result<SnipConfig>
+    where
+        F: FnOnce(&mut SnipConfig) -> anyhow::Result<()>,
+    {
+        let _lock = FileLock::acquire(Path::new(path))?;
+        let mut config = SnipConfig::load(path)?;
+        change(&mut config)?;
+        config.write(path)?;
+        Ok(config)
+    }
+
+    fn write(&self, path: &str) -> anyhow::Result<()> {
         let config_content =
             serde_json::to_string_pretty(self).context("Failed to serialize config")?;
-        fs::write(path, config_content).context("Failed to write config file")?;
+        atomic_write(Path::new(path), &config_content).context("Failed to write config file")?;
         Ok(())
     }
 
-    pub fn update_path(&mut self, new_path: String) {
-        self.path = expand_home_dir(&new_path).to_string_lossy().into_owned();
+    /// The collection picked by `--collection`, else the first one for `--lang`, else the
+    /// active one. Falling back keeps `--lang` usable to filter multi-language files.
+    pub fn select(
+        &self,
+        name: Option<&str>,
+        lang: Option<&str>,
+    ) -> anyhow::Result<(&str, &Collection)> {
+        let found = match name {
+            Some(name) => self.collections.get_key_value(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Collection '{}' not found, available: {}",
+                    name,
+                    self.names()
+                )
+            })?,
+            None => lang
+                .and_then(|lang| {
+                    self.collections
+                        .iter()
+                        .find(|(_, collection)| collection.lang == lang)
+                })
+                .or_else(|| self.collections.get_key_value(&self.active))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("The active collection '{}' is not configured", self.active)
+                })?,
+        };
+        Ok((found.0.as_str(), found.1))
+    }
+
+    fn names(&self) -> String {
+        self.collections
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn update_path(&mut self, name: &str, new_path: String) -> anyhow::Result<()> {
+        let collection = self
+            .collections
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Collection '{}' not found", name))?;
+        collection.path = expand_home_dir(&new_path).to_string_lossy().into_owned();
+        Ok(())
+    }
+
+    pub fn add_collection(
+        &mut self,
+        name: String,
+        path: String,
+        lang: Option<String>,
+        extensions: Vec<String>,
+    ) -> anyhow::Result<()> {
+        if self.collections.contains_key(&name) {
+            return Err(anyhow::anyhow!("Collection '{}' already exists", name));
+        }
+        let path = expand_home_dir(&path).to_string_lossy().into_owned();
+        let collection = Collection {
+            lang: lang.unwrap_or_else(|| lang_from_path(&path)),
+            path,
+            extensions,
+        };
+        self.collections.insert(name, collection);
+        Ok(())
+    }
+
+    pub fn remove_collection(&mut self, name: &str) -> anyhow::Result<Collection> {
+        if name == self.active {
+            return Err(anyhow::anyhow!(
+                "Collection '{}' is active, switch to another one with `snip config use` first",
+                name
+            ));
+        }
+        self.collections
+            .remove(name)
+            .ok_or_else(|| anyhow::anyhow!("Collection '{}' not found", name))
+    }
+
+    pub fn use_collection(&mut self, name: &str) -> anyhow::Result<()> {
+        if !self.collections.contains_key(name) {
+            return Err(anyhow::anyhow!(
+                "Collection '{}' not found, available: {}",
+                name,
+                self.names()
+            ));
+        }
+        self.active = name.to_string();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_or_init_only_initializes_empty_file() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+
+        fs::write(config_path, "").unwrap();
+        let config = SnipConfig::load_or_init(config_path).unwrap();
+        assert_eq!(config.collections, SnipConfig::default_config().collections);
+
+        let broken = "{ \"path\": \"/snippets/rust.json\", }";
+        fs::write(config_path, broken).unwrap();
+        let error = SnipConfig::load_or_init(config_path).unwrap_err();
+        assert!(format!("{:#}", error).contains("line 1 column"));
+        assert_eq!(fs::read_to_string(config_path).unwrap(), broken);
+
+        fs::write(config_path, "\n").unwrap();
+        assert!(SnipConfig::load_or_init(config_path).is_err());
+        assert_eq!(fs::read_to_string(config_path).unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_load_or_init_migrates_single_path_config() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+        fs::write(config_path, r#"{ "path": "/snippets/python.json" }"#).unwrap();
+
+        let config = SnipConfig::load_or_init(config_path).unwrap();
+        let (name, collection) = config.select(None, None).unwrap();
+        assert_eq!(name, "python");
+        assert_eq!(collection.path, "/snippets/python.json");
+
+        let saved = fs::read_to_string(config_path).unwrap();
+        assert!(saved.contains("\"collections\""));
+        assert!(!saved.contains("\"path\": \"/snippets/python.json\",\n  \"active\""));
+    }
+
+    #[test]
+    fn test_select_collection() {
+        let mut config = SnipConfig::with_path("/snippets/rust.json".to_string());
+        config
+            .add_collection(
+                "web".to_string(),
+                "/snippets/ts.json".to_string(),
+                Some("typescript".to_string()),
+                vec!["ts".to_string()],
+            )
+            .unwrap();
+
+        assert_eq!(config.select(None, None).unwrap().0, "rust");
+        assert_eq!(config.select(Some("web"), None).unwrap().0, "web");
+        assert_eq!(config.select(None, Some("typescript")).unwrap().0, "web");
+        assert_eq!(config.select(None, Some("toml")).unwrap().0, "rust");
+        assert!(config.select(Some("python"), None).is_err());
+
+        config.use_collection("web").unwrap();
+        assert!(config.remove_collection("web").is_err());
+        assert!(config.remove_collection("rust").is_ok());
+    }
+
+    #[test]
+    fn test_repair_keeps_path_and_backup() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+        let broken = "{\n  // mine\n  \"path\": \"/snippets/rust.json\",\n}";
+        fs::write(config_path, broken).unwrap();
+
+        let (config, backup) = SnipConfig::repair(config_path).unwrap();
+
+        assert_eq!(
+            config.select(None, None).unwrap().1.path,
+            "/snippets/rust.json"
+        );
+        assert_eq!(
+            SnipConfig::load(config_path).unwrap().collections,
+            config.collections
+        );
+        assert_eq!(fs::read_to_string(backup).unwrap(), broken);
+    }
+
+    #[test]
+    fn test_reset_writes_defaults() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+        fs::write(config_path, "not json").unwrap();
+
+        assert!(SnipConfig::repair(config_path).is_err());
+        let (config, backup) = SnipConfig::reset(config_path).unwrap();
+
+        assert_eq!(config.collections, SnipConfig::default_config().collections);
+        assert_eq!(fs::read_to_string(backup).unwrap(), "not json");
     }
 }
@@ -0,0 +1,198 @@
+use crate::helpers::hash_contents::hash_contents;
+use crate::models::snippet_document_model::SnippetDocument;
+use crate::models::snippet_model::Snippet;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{self, Path};
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Add,
+    Edit,
+    Remove,
+    Rename,
+    /// A rewrite of the whole file, e.g. by `snip fmt`
+    Format,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Add => "add",
+            Operation::Edit => "edit",
+            Operation::Remove => "remove",
+            Operation::Rename => "rename",
+            Operation::Format => "format",
+        }
+    }
+}
+
+/// A snippet together with the key it is stored under.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SnippetEntry {
+    pub key: String,
+    pub snippet: Snippet,
+}
+
+impl SnippetEntry {
+    pub fn new(key: &str, snippet: &Snippet) -> SnippetEntry {
+        SnippetEntry {
+            key: key.to_string(),
+            snippet: snippet.clone(),
+        }
+    }
+}
+
+/// What a mutating action did to a collection file, so it can be journaled and undone.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SnippetChange {
+    pub path: String,
+    pub operation: Operation,
+    pub before: Option<SnippetEntry>,
+    pub after: Option<SnippetEntry>,
+    /// Hash of the file while the change is not applied
+    pub before_hash: String,
+    /// Hash of the file while the change is applied
+    pub after_hash: String,
+    /// The whole file before and after, so replaying the change gives back the exact bytes.
+    /// Missing in entries journaled before it was kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texts: Option<(String, String)>,
+}
+
+impl SnippetChange {
+    /// `path` is journaled absolute and with symlinks resolved, so the change is found again
+    /// whatever directory and spelling it is given from.
+    pub fn new(
+        path: &str,
+        operation: Operation,
+        before: Option<SnippetEntry>,
+        after: Option<SnippetEntry>,
+        before_text: &str,
+        after_text: &str,
+    ) -> SnippetChange {
+        let path = fs::canonicalize(path)
+            .or_else(|_| path::absolute(path))
+            .unwrap_or_else(|_| Path::new(path).to_path_buf());
+        SnippetChange {
+            path: path.to_string_lossy().into_owned(),
+            operation,
+            before,
+            after,
+            before_hash: hash_contents(before_text),
+            after_hash: hash_contents(after_text),
+            texts: Some((before_text.to_string(), after_text.to_string())),
+        }
+    }
+
+    /// A change that rewrites the whole file at `path`.
+    pub fn whole_file(
+        path: &str,
+        operation: Operation,
+        before_text: &str,
+        after_text: &str,
+    ) -> SnippetChange {
+        SnippetChange::new(path, operation, None, None, before_text, after_text)
+    }
+
+    /// The key the change is about, as it is after the change.
+    pub fn key(&self) -> &str {
+        self.after
+            .as_ref()
+            .or(self.before.as_ref())
+            .map(|entry| entry.key.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The operation and the key it is about, e.g. `add 'key'`, or `format` for whole files.
+    pub fn summary(&self) -> String {
+        match self.before.is_none() && self.after.is_none() {
+            true => self.operation.as_str().to_string(),
+            false => format!("{} '{}'", self.operation.as_str(), self.key()),
+        }
+    }
+
+    /// Brings `document` from the `before` state to the `after` state.
+    pub fn apply(&self, document: &mut SnippetDocument) -> anyhow::Result<()> {
+        if let Some((_, after_text)) = &self.texts {
+            *document = SnippetDocument::parse(after_text.clone())?;
+            return Ok(());
+        }
+        transition(document, self.before.as_ref(), self.after.as_ref())
+    }
+
+    /// Brings `document` from the `after` state back to the `before` state.
+    pub fn revert(&self, document: &mut SnippetDocument) -> anyhow::Result<()> {
+        if let Some((before_text, _)) = &self.texts {
+            *document = SnippetDocument::parse(before_text.clone())?;
+            return Ok(());
+        }
+        transition(document, self.after.as_ref(), self.before.as_ref())
+    }
+}
+
+fn transition(
+    document: &mut SnippetDocument,
+    from: Option<&SnippetEntry>,
+    to: Option<&SnippetEntry>,
+) -> anyhow::Result<()> {
+    match (from, to) {
+        (None, Some(to)) => document.insert(&to.key, &to.snippet),
+        (Some(from), None) => document.remove(&from.key).map(|_| ()),
+        (Some(from), Some(to)) => {
+            if from.key != to.key {
+                document.rename(&from.key, &to.key)?;
+            }
+            document.replace(&to.key, &to.snippet)
+        }
+        (None, None) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_and_revert_edit() {
+        let text = "{\n  \"a\": { \"prefix\": \"a\", \"body\": [\"old\"] }\n}\n";
+        let mut document = SnippetDocument::parse(text.to_string()).unwrap();
+        let before = SnippetEntry::new("a", document.get("a").unwrap());
+        let mut after = before.clone();
+        after.key = "b".to_string();
+        after.snippet.body.replace(vec!["new".to_string()]);
+        // As journaled before the texts were kept
+        let change = SnippetChange {
+            texts: None,
+            ..SnippetChange::new(
+                "rust.json",
+                Operation::Edit,
+                Some(before),
+                Some(after),
+                "",
+                "",
+            )
+        };
+
+        change.apply(&mut document).unwrap();
+        assert_eq!(document.get("b").unwrap().body_text(), "new");
+
+        change.revert(&mut document).unwrap();
+        assert_eq!(document.text(), text);
+    }
+
+    #[test]
+    fn test_new_journals_canonical_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let path = temp_dir.path().join("rust.json");
+        fs::write(&path, "{}").unwrap();
+        let typed = temp_dir.path().join("sub/../rust.json");
+
+        let change = SnippetChange::whole_file(typed.to_str().unwrap(), Operation::Format, "", "");
+
+        let canonical = fs::canonicalize(&path).unwrap();
+        assert_eq!(change.path, canonical.to_str().unwrap());
+    }
+}
@@ -0,0 +1,324 @@
+use crate::helpers::jsonc::{self, JsonNode, Style};
+use crate::models::snippet_model::Snippet;
+use anyhow::Context;
+use serde_json::Value;
+
+/// A snippet collection file as text, with the snippets it holds.
+///
+/// Files may contain `//` comments and trailing commas. Edits only rewrite the part of the
+/// text that changes, so comments, key order, indentation and line endings are kept.
+#[derive(Debug, Clone, Default)]
+pub struct SnippetDocument {
+    text: String,
+    root: Option<JsonNode>,
+    snippets: Vec<(String, Snippet)>,
+}
+
+impl SnippetDocument {
+    pub fn parse(text: String) -> anyhow::Result<SnippetDocument> {
+        if text.trim().is_empty() {
+            return Ok(SnippetDocument {
+                text,
+                ..Default::default()
+            });
+        }
+
+        let root = jsonc::parse(&text)?;
+        if !matches!(root, JsonNode::Object { .. }) {
+            return Err(anyhow::anyhow!(
+                "Expected the snippets file to contain a JSON object"
+            ));
+        }
+
+        let mut snippets: Vec<(String, Snippet)> = Vec::new();
+        for member in root.members() {
+            let snippet: Snippet =
+                serde_json::from_value(member.value.to_value()).with_context(|| {
+                    let (line, _) = jsonc::line_col(&text, member.key_span.start);
+                    format!("Invalid snippet '{}' at line {}", member.key, line)
+                })?;
+            // Like serde, the last entry wins when a key is duplicated
+            match snippets.iter_mut().find(|(key, _)| *key == member.key) {
+                Some(entry) => entry.1 = snippet,
+                None => snippets.push((member.key.clone(), snippet)),
+            }
+        }
+
+        Ok(SnippetDocument {
+            text,
+            root: Some(root),
+            snippets,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn root(&self) -> Option<&JsonNode> {
+        self.root.as_ref()
+    }
+
+    /// Snippets in file order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Snippet)> {
+        self.snippets
+            .iter()
+            .map(|(key, snippet)| (key.as_str(), snippet))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Snippet> {
+        self.iter().map(|(_, snippet)| snippet)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Snippet> {
+        self.iter().find(|(k, _)| *k == key).map(|(_, s)| s)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snippets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snippets.is_empty()
+    }
+
+    fn style(&self) -> Style {
+        Style::detect(&self.text, self.root.as_ref())
+    }
+
+    fn update_text(&mut self, text: String) -> anyhow::Result<()> {
+        *self = SnippetDocument::parse(text).context("Failed to update the snippets file")?;
+        Ok(())
+    }
+
+    fn snippet_member(&self, key: &str) -> anyhow::Result<&jsonc::JsonMember> {
+        self.root
+            .as_ref()
+            .and_then(|root| root.member(key))
+            .ok_or_else(|| anyhow::anyhow!("Snippet with key '{}' not found", key))
+    }
+
+    /// Whether the keys are sorted, as written by `snip fmt`. Sorted files are kept sorted.
+    pub fn is_sorted(&self) -> bool {
+        self.root
+            .as_ref()
+            .is_some_and(|root| root.members().is_sorted_by(|a, b| a.key <= b.key))
+    }
+
+    /// Adds a snippet at its place in a sorted collection, at the end of other ones.
+    pub fn insert(&mut self, key: &str, snippet: &Snippet) -> anyhow::Result<()> {
+        if self.contains_key(key) {
+            return Err(anyhow::anyhow!(
+                "A snippet with key '{}' already exists",
+                key
+            ));
+        }
+        let value = serde_json::to_value(snippet).context("Failed to serialize snippet")?;
+        let style = self.style();
+        let next = self
+            .root
+            .as_ref()
+            .filter(|_| self.is_sorted())
+            .and_then(|root| root.members().iter().position(|m| m.key.as_str() > key));
+        let text = match (&self.root, next) {
+            (Some(root), Some(index)) => {
+                jsonc::insert_member_before(&self.text, root, index, key, &value, &style)
+            }
+            (Some(root), None) => jsonc::insert_member(&self.text, root, key, &value, &style),
+            (None, _) => {
+                let root = jsonc::parse("{}").expect("an empty object is valid JSON");
+                let text = jsonc::insert_member("{}", &root, key, &value, &style);
+                format!("{}{}", text, style.eol)
+            }
+        };
+        self.update_text(text)
+    }
+
+    /// Replaces a snippet, only rewriting the fields whose value changed.
+    pub fn replace(&mut self, key: &str, snippet: &Snippet) -> anyhow::Result<()> {
+        let Value::Object(fields) =
+            serde_json::to_value(snippet).context("Failed to serialize snippet")?
+        else {
+            unreachable!("a snippet serializes to a JSON object");
+        };
+        let style = self.style();
+
+        for (field, value) in &fields {
+            let member = self.snippet_member(key)?;
+            let text = match member.value.member(field) {
+                Some(current) if current.value.to_value() == *value => continue,
+                Some(current) => jsonc::replace_value(&self.text, current, value, &style),
+                None => jsonc::insert_member(&self.text, &member.value, field, value, &style),
+            };
+            self.update_text(text)?;
+        }
+
+        loop {
+            let member = self.snippet_member(key)?;
+            let Some(index) = member
+                .value
+                .members()
+                .iter()
+                .position(|m| !fields.contains_key(&m.key))
+            else {
+                break;
+            };
+            let text = jsonc::remove_member(&self.text, &member.value, index);
+            self.update_text(text)?;
+        }
+        Ok(())
+    }
+
+    pub fn rename(&mut self, old_key: &str, new_key: &str) -> anyhow::Result<()> {
+        if self.contains_key(new_key) {
+            return Err(anyhow::anyhow!(
+                "A snippet with key '{}' already exists",
+                new_key
+            ));
+        }
+        // A sorted file stays sorted: the snippet moves to the place of its new key
+        if self.is_sorted() && self.len() > 1 {
+            let snippet = self.remove(old_key)?;
+            return self.insert(new_key, &snippet);
+        }
+        let text = jsonc::rename_member(&self.text, self.snippet_member(old_key)?, new_key);
+        self.update_text(text)
+    }
+
+    pub fn remove(&mut self, key: &str) -> anyhow::Result<Snippet> {
+        let snippet = self
+            .get(key)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Snippet with key '{}' not found", key))?;
+        // Drop every entry for the key, duplicates included, so it doesn't come back
+        while let Some(root) = &self.root {
+            let Some(index) = root.members().iter().position(|m| m.key == key) else {
+                break;
+            };
+            let text = jsonc::remove_member(&self.text, root, index);
+            self.update_text(text)?;
+        }
+        Ok(snippet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"{
+    // Iterators
+    "impl_iterator": {
+        "prefix": "impl_iterator",
+        "body": ["impl Iterator for $1 {}"], // one line
+        "scope": "rust",
+    },
+    "impl_deref": {
+        "prefix": ["impl_deref", "deref"],
+        "body": "impl Deref for $1 {}",
+        "description": "Impl Deref",
+    },
+}
+"#;
+
+    fn snippet(prefix: &str, body: &str) -> Snippet {
+        Snippet {
+            prefix: prefix.into(),
+            body: vec![body.to_string()].into(),
+            description: Some(format!("{} description", prefix)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_keeps_file_order() {
+        let document = SnippetDocument::parse(TEXT.to_string()).unwrap();
+
+        assert_eq!(
+            document.keys().collect::<Vec<_>>(),
+            vec!["impl_iterator", "impl_deref"]
+        );
+        assert_eq!(
+            document.get("impl_iterator").unwrap().scope.as_deref(),
+            Some("rust")
+        );
+    }
+
+    #[test]
+    fn test_insert_into_empty_document() {
+        let mut document = SnippetDocument::parse(String::new()).unwrap();
+        document.insert("a", &snippet("a", "body")).unwrap();
+
+        assert_eq!(
+            document.text(),
+            "{\n  \"a\": {\n    \"prefix\": \"a\",\n    \"body\": [\n      \"body\"\n    ],\n    \"description\": \"a description\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_sorted_document_stays_sorted() {
+        let mut document = SnippetDocument::parse(
+            "{\n  \"b\": { \"prefix\": \"b\", \"body\": \"b\" }\n}\n".to_string(),
+        )
+        .unwrap();
+        document.insert("a", &snippet("a", "a")).unwrap();
+        document.insert("c", &snippet("c", "c")).unwrap();
+        assert_eq!(document.keys().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+
+        document.rename("a", "d").unwrap();
+        assert_eq!(document.keys().collect::<Vec<_>>(), vec!["b", "c", "d"]);
+        assert!(document.is_sorted());
+    }
+
+    #[test]
+    fn test_replace_only_changes_edited_fields() {
+        let mut document = SnippetDocument::parse(TEXT.to_string()).unwrap();
+        let mut edited = document.get("impl_iterator").unwrap().clone();
+        edited.description = Some("Impl Iterator".to_string());
+        document.replace("impl_iterator", &edited).unwrap();
+
+        assert_eq!(
+            document.text(),
+            TEXT.replace(
+                "        \"scope\": \"rust\",\n",
+                "        \"scope\": \"rust\",\n        \"description\": \"Impl Iterator\",\n"
+            )
+        );
+
+        edited.description = None;
+        document.replace("impl_iterator", &edited).unwrap();
+        assert_eq!(document.text(), TEXT);
+    }
+
+    #[test]
+    fn test_rename_and_remove() {
+        let mut document = SnippetDocument::parse(TEXT.to_string()).unwrap();
+        document.rename("impl_deref", "deref").unwrap();
+        assert_eq!(
+            document.text(),
+            TEXT.replace("\"impl_deref\": {", "\"deref\": {")
+        );
+
+        document.remove("impl_iterator").unwrap();
+        assert_eq!(
+            document.text(),
+            r#"{
+    // Iterators
+    "deref": {
+        "prefix": ["impl_deref", "deref"],
+        "body": "impl Deref for $1 {}",
+        "description": "Impl Deref",
+    },
+}
+"#
+        );
+    }
+}
@@ -1,8 +1,108 @@
+use crate::models::string_or_vec_model::StringOrVec;
+use crate::parser::parse_snippet_body::parse_snippet_body;
+use crate::parser::snippet_ast::SnippetAst;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Snippet {
-    pub prefix: String,
-    pub body: Vec<String>,
-    pub description: String,
+    pub prefix: StringOrVec,
+    pub body: StringOrVec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Comma-separated language ids the snippet is offered for, e.g. `rust,toml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Fields `snip` doesn't model (e.g. `isFileTemplate`, `luasnip`), written back as-is.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Snippet {
+    pub fn prefixes(&self) -> impl Iterator<Item = &str> {
+        self.prefix.iter()
+    }
+
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    /// Language ids from the `scope` field, empty when the snippet applies to every language.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+    }
+
+    /// Tags from a `tags` field holding a string or an array of strings, which is otherwise
+    /// kept as-is like other unmodeled fields.
+    pub fn tags(&self) -> Vec<&str> {
+        match self.extra.get("tags") {
+            Some(Value::String(tag)) => vec![tag],
+            Some(Value::Array(tags)) => tags.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the snippet should be offered for files of the language `lang`.
+    pub fn applies_to(&self, lang: &str) -> bool {
+        let mut scopes = self.scopes().peekable();
+        scopes.peek().is_none() || scopes.any(|scope| scope == lang)
+    }
+
+    /// The body as the editor inserts it: array entries are joined with newlines.
+    pub fn body_text(&self) -> String {
+        self.body.join("\n")
+    }
+
+    /// The body parsed with the TextMate/VSCode snippet grammar, spans point into `body_text`.
+    pub fn body_ast(&self) -> SnippetAst {
+        parse_snippet_body(&self.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_vscode_shapes() {
+        let snippet: Snippet =
+            serde_json::from_str(r#"{ "prefix": ["impl", "im"], "body": "single line" }"#).unwrap();
+
+        assert_eq!(snippet.prefixes().collect::<Vec<_>>(), vec!["impl", "im"]);
+        assert_eq!(snippet.body_text(), "single line");
+        assert_eq!(snippet.description(), "");
+        assert_eq!(
+            serde_json::to_string(&snippet).unwrap(),
+            r#"{"prefix":["impl","im"],"body":"single line"}"#
+        );
+    }
+
+    #[test]
+    fn test_applies_to_scoped_languages() {
+        let mut snippet: Snippet =
+            serde_json::from_str(r#"{ "prefix": "p", "body": "", "scope": "rust, toml" }"#)
+                .unwrap();
+
+        assert!(snippet.applies_to("rust"));
+        assert!(snippet.applies_to("toml"));
+        assert!(!snippet.applies_to("python"));
+
+        snippet.scope = None;
+        assert!(snippet.applies_to("python"));
+    }
+
+    #[test]
+    fn test_unknown_fields_round_trip() {
+        let json = r#"{"prefix":"fn","body":["fn $1() {}"],"scope":"rust","luasnip":{"priority":1000},"isFileTemplate":false}"#;
+        let snippet: Snippet = serde_json::from_str(json).unwrap();
+
+        assert_eq!(snippet.scope.as_deref(), Some("rust"));
+        assert_eq!(snippet.extra["isFileTemplate"], false);
+        assert_eq!(serde_json::to_string(&snippet).unwrap(), json);
+    }
 }
@@ -0,0 +1,50 @@
+use crate::models::layer_model::Layer;
+use crate::models::snippet_model::Snippet;
+use crate::models::snippet_source_model::SnippetSource;
+use serde::Serialize;
+
+/// A snippet as printed by `--output json|jsonl`. Fields are always present so scripts
+/// don't have to handle the different shapes of the snippet files.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SnippetRecord {
+    pub key: String,
+    pub prefixes: Vec<String>,
+    pub description: Option<String>,
+    pub body: Vec<String>,
+    /// Languages the snippet is offered for, empty for every language
+    pub scope: Vec<String>,
+    pub layer: Layer,
+    /// Path of the file the snippet comes from
+    pub source: String,
+}
+
+impl SnippetRecord {
+    pub fn new(key: &str, snippet: &Snippet, source: &SnippetSource) -> SnippetRecord {
+        SnippetRecord {
+            key: key.to_string(),
+            prefixes: snippet.prefix.to_vec(),
+            description: snippet.description.clone(),
+            body: snippet.body.to_vec(),
+            scope: snippet.scopes().map(str::to_string).collect(),
+            layer: source.layer,
+            source: source.path.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_has_stable_shape() {
+        let snippet: Snippet =
+            serde_json::from_str(r#"{ "prefix": "fn", "body": "fn $1() {}" }"#).unwrap();
+        let record = SnippetRecord::new("fn", &snippet, &SnippetSource::user("rust.json"));
+
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"key":"fn","prefixes":["fn"],"description":null,"body":["fn $1() {}"],"scope":[],"layer":"user","source":"rust.json"}"#
+        );
+    }
+}
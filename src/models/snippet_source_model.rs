@@ -0,0 +1,21 @@
+use crate::models::layer_model::Layer;
+
+/// A snippet file and the layer it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetSource {
+    pub layer: Layer,
+    pub path: String,
+}
+
+impl SnippetSource {
+    pub fn new(layer: Layer, path: impl Into<String>) -> SnippetSource {
+        SnippetSource {
+            layer,
+            path: path.into(),
+        }
+    }
+
+    pub fn user(path: impl Into<String>) -> SnippetSource {
+        SnippetSource::new(Layer::User, path)
+    }
+}
@@ -0,0 +1,133 @@
+use crate::models::snippet_model::Snippet;
+
+/// Marks the end of the header of a snippet template, the body follows it.
+const BODY_MARKER: &str = "--- body below ---";
+
+/// A snippet as a text document for `--interactive` editing: a header with its fields as
+/// comments, then the raw body.
+///
+/// ```text
+/// // key: impl_deref
+/// // prefix: impl_deref, deref
+/// // description: Impl Deref
+/// // scope:
+/// // --- body below ---
+/// impl Deref for ${1:T} {}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnippetTemplate {
+    pub key: String,
+    pub prefixes: Vec<String>,
+    pub description: String,
+    pub scope: String,
+    pub body: Vec<String>,
+}
+
+impl SnippetTemplate {
+    pub fn new(key: &str, snippet: &Snippet) -> SnippetTemplate {
+        SnippetTemplate {
+            key: key.to_string(),
+            prefixes: snippet.prefix.to_vec(),
+            description: snippet.description().to_string(),
+            scope: snippet.scope.clone().unwrap_or_default(),
+            body: snippet.body.to_vec(),
+        }
+    }
+
+    /// The document, header lines start with the language's line `comment`.
+    pub fn render(&self, comment: &str) -> String {
+        let mut text = format!(
+            "{c} key: {}\n{c} prefix: {}\n{c} description: {}\n{c} scope: {}\n{c} {}\n",
+            self.key,
+            self.prefixes.join(", "),
+            self.description,
+            self.scope,
+            BODY_MARKER,
+            c = comment
+        );
+        for line in &self.body {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn parse(text: &str, comment: &str) -> anyhow::Result<SnippetTemplate> {
+        let mut template = SnippetTemplate::default();
+        let mut lines = text.lines();
+        loop {
+            let line = lines.next().ok_or_else(|| {
+                anyhow::anyhow!("The '{} {}' line is missing", comment, BODY_MARKER)
+            })?;
+            let field = line.trim().trim_start_matches(comment).trim();
+            if field == BODY_MARKER {
+                break;
+            }
+            let Some((name, value)) = field.split_once(':') else {
+                return Err(anyhow::anyhow!(
+                    "Expected `name: value` in the header, got '{}'",
+                    line
+                ));
+            };
+            let value = value.trim().to_string();
+            match name.trim() {
+                "key" => template.key = value,
+                "prefix" => {
+                    template.prefixes = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|prefix| !prefix.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "description" => template.description = value,
+                "scope" => template.scope = value,
+                name => return Err(anyhow::anyhow!("Unknown field '{}' in the header", name)),
+            }
+        }
+        template.body = lines.map(str::to_string).collect();
+
+        if template.key.is_empty() {
+            return Err(anyhow::anyhow!("The key is empty"));
+        }
+        if template.prefixes.is_empty() {
+            return Err(anyhow::anyhow!("The snippet needs at least one prefix"));
+        }
+        if template.body.iter().all(|line| line.trim().is_empty()) {
+            return Err(anyhow::anyhow!("The body is empty"));
+        }
+        Ok(template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_parse_template() {
+        let snippet = Snippet {
+            prefix: vec!["impl_deref".to_string(), "deref".to_string()].into(),
+            body: vec!["impl Deref for ${1:T} {".to_string(), "}".to_string()].into(),
+            description: Some("Impl Deref".to_string()),
+            ..Default::default()
+        };
+        let template = SnippetTemplate::new("impl_deref", &snippet);
+        let text = template.render("#");
+
+        assert_eq!(
+            text,
+            "# key: impl_deref\n# prefix: impl_deref, deref\n# description: Impl Deref\n# scope: \n# --- body below ---\nimpl Deref for ${1:T} {\n}\n"
+        );
+        assert_eq!(SnippetTemplate::parse(&text, "#").unwrap(), template);
+
+        let edited = text
+            .replace("# scope: ", "# scope: rust")
+            .replace(", deref\n", "\n");
+        let parsed = SnippetTemplate::parse(&edited, "#").unwrap();
+        assert_eq!(parsed.scope, "rust");
+        assert_eq!(parsed.prefixes, vec!["impl_deref"]);
+
+        assert!(SnippetTemplate::parse(&SnippetTemplate::default().render("//"), "//").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+/// A snippet field that VSCode accepts either as a single string or as an array of strings,
+/// e.g. `"prefix": "impl"` and `"prefix": ["impl", "im"]`.
+///
+/// The variant read from the file is kept, so serializing writes it back in the same shape.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum StringOrVec {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl StringOrVec {
+    /// Builds a value from CLI input: one value becomes a plain string, several an array.
+    pub fn from_values(mut values: Vec<String>) -> Self {
+        if values.len() == 1 {
+            StringOrVec::Single(values.remove(0))
+        } else {
+            StringOrVec::Multiple(values)
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let values: &[String] = match self {
+            StringOrVec::Single(value) => std::slice::from_ref(value),
+            StringOrVec::Multiple(values) => values,
+        };
+        values.iter().map(String::as_str)
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        self.iter().map(str::to_owned).collect()
+    }
+
+    pub fn contains(&self, value: &str) -> bool {
+        self.iter().any(|v| v == value)
+    }
+
+    pub fn join(&self, separator: &str) -> String {
+        self.to_vec().join(separator)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Replaces the values, keeping a plain string when it was one and still fits.
+    pub fn replace(&mut self, mut values: Vec<String>) {
+        *self = match self {
+            StringOrVec::Single(_) if values.len() == 1 => StringOrVec::Single(values.remove(0)),
+            _ => StringOrVec::Multiple(values),
+        };
+    }
+}
+
+impl Default for StringOrVec {
+    fn default() -> Self {
+        StringOrVec::Multiple(Vec::new())
+    }
+}
+
+impl From<&str> for StringOrVec {
+    fn from(value: &str) -> Self {
+        StringOrVec::Single(value.to_string())
+    }
+}
+
+impl From<String> for StringOrVec {
+    fn from(value: String) -> Self {
+        StringOrVec::Single(value)
+    }
+}
+
+impl From<Vec<String>> for StringOrVec {
+    fn from(values: Vec<String>) -> Self {
+        StringOrVec::Multiple(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_keeps_shape() {
+        let single: StringOrVec = serde_json::from_str(r#""impl""#).unwrap();
+        let multiple: StringOrVec = serde_json::from_str(r#"["impl", "im"]"#).unwrap();
+
+        assert_eq!(single, StringOrVec::Single("impl".to_string()));
+        assert_eq!(multiple.to_vec(), vec!["impl", "im"]);
+        assert_eq!(serde_json::to_string(&single).unwrap(), r#""impl""#);
+        assert_eq!(
+            serde_json::to_string(&multiple).unwrap(),
+            r#"["impl","im"]"#
+        );
+    }
+
+    #[test]
+    fn test_replace_keeps_single_string_when_possible() {
+        let mut value = StringOrVec::from("old");
+        value.replace(vec!["new".to_string()]);
+        assert_eq!(value, StringOrVec::Single("new".to_string()));
+
+        value.replace(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(
+            value,
+            StringOrVec::Multiple(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use crate::helpers::fuzzy_match::fuzzy_match;
+use crate::models::collection_model::Collection;
+use crate::models::layered_snippets_model::LayeredSnippets;
+use crate::models::snippet_model::Snippet;
+use crate::models::snippet_source_model::SnippetSource;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::cmp::Reverse;
+
+/// A collection `snip tui` can switch to, with the layered files its snippets come from.
+#[derive(Debug, Clone)]
+pub struct TuiCollection {
+    pub name: String,
+    pub collection: Collection,
+    pub sources: Vec<SnippetSource>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuiEntry {
+    pub key: String,
+    pub snippet: Snippet,
+    pub source: SnippetSource,
+}
+
+/// What the keys typed go to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TuiMode {
+    #[default]
+    Filter,
+    /// The new key of the selected snippet
+    Rename(String),
+    /// The key of the copy of the selected snippet
+    Duplicate(String),
+    ConfirmDelete,
+}
+
+/// Work a key asks for that reads or writes files, done by `run_tui`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TuiAction {
+    Quit,
+    Copy,
+    Edit,
+    Rename(String),
+    Duplicate(String),
+    Delete,
+    /// Loads the snippets of the current collection again
+    Reload,
+}
+
+#[derive(Debug)]
+pub struct TuiState {
+    pub collections: Vec<TuiCollection>,
+    /// Index of the collection shown
+    pub current: usize,
+    pub entries: Vec<TuiEntry>,
+    /// Indices of the entries matching the query, in display order
+    pub matches: Vec<usize>,
+    /// Index into `matches`
+    pub selected: usize,
+    pub query: String,
+    pub mode: TuiMode,
+    /// Result of the last action, shown instead of the key help
+    pub status: Option<String>,
+}
+
+impl TuiState {
+    /// Opens on the collection at `current`. A collection that fails to load is shown empty,
+    /// with the error in the status line.
+    pub fn new(collections: Vec<TuiCollection>, current: usize) -> TuiState {
+        let mut state = TuiState {
+            collections,
+            current,
+            entries: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+            query: String::new(),
+            mode: TuiMode::default(),
+            status: None,
+        };
+        if let Err(error) = state.reload() {
+            state.status = Some(format!("Error: {:#}", error));
+        }
+        state
+    }
+
+    pub fn collection(&self) -> &TuiCollection {
+        &self.collections[self.current]
+    }
+
+    /// Reads the snippets of the current collection again, keeping the selected key when
+    /// it is still there.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let selected_key = self.selected_entry().map(|entry| entry.key.clone());
+        self.entries.clear();
+        let loaded = LayeredSnippets::load(&self.collection().sources);
+        if let Ok(snippets) = &loaded {
+            self.entries = snippets
+                .iter()
+                .map(|(key, snippet, source)| TuiEntry {
+                    key: key.to_string(),
+                    snippet: snippet.clone(),
+                    source: source.clone(),
+                })
+                .collect();
+        }
+        self.filter();
+        if let Some(key) = selected_key {
+            self.select_key(&key);
+        }
+        loaded.map(|_| ())
+    }
+
+    pub fn selected_entry(&self) -> Option<&TuiEntry> {
+        self.matches
+            .get(self.selected)
+            .map(|&index| &self.entries[index])
+    }
+
+    pub fn select_key(&mut self, key: &str) {
+        if let Some(position) = self
+            .matches
+            .iter()
+            .position(|&index| self.entries[index].key == key)
+        {
+            self.selected = position;
+        }
+    }
+
+    /// Keeps the entries whose key, a prefix, the description or the body fuzzy matches the
+    /// query, best matches first.
+    fn filter(&mut self) {
+        let mut scored: Vec<(i32, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let snippet = &entry.snippet;
+                let body = snippet.body_text();
+                std::iter::once(entry.key.as_str())
+                    .chain(snippet.prefixes())
+                    .chain([snippet.description(), body.as_str()])
+                    .filter_map(|field| fuzzy_match(field, &self.query))
+                    .map(|found| found.score)
+                    .max()
+                    .map(|score| (score, index))
+            })
+            .collect();
+        scored.sort_by_key(|&(score, _)| Reverse(score));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        self.selected = match down {
+            true if self.selected + 1 < self.matches.len() => self.selected + 1,
+            false => self.selected.saturating_sub(1),
+            true => self.selected,
+        };
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<TuiAction> {
+        // Terminals reporting releases would run every action twice
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('c') {
+            return Some(TuiAction::Quit);
+        }
+        match &mut self.mode {
+            TuiMode::Filter => self.handle_filter_key(key.code, ctrl),
+            TuiMode::Rename(input) | TuiMode::Duplicate(input) => match key.code {
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    None
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    None
+                }
+                KeyCode::Esc => {
+                    self.mode = TuiMode::Filter;
+                    None
+                }
+                KeyCode::Enter => match std::mem::take(&mut self.mode) {
+                    TuiMode::Rename(new_key) => Some(TuiAction::Rename(new_key)),
+                    TuiMode::Duplicate(new_key) => Some(TuiAction::Duplicate(new_key)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            TuiMode::ConfirmDelete => {
+                self.mode = TuiMode::Filter;
+                matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y'))
+                    .then_some(TuiAction::Delete)
+            }
+        }
+    }
+
+    fn handle_filter_key(&mut self, code: KeyCode, ctrl: bool) -> Option<TuiAction> {
+        self.status = None;
+        let key = self.selected_entry().map(|entry| entry.key.clone());
+        match (code, ctrl) {
+            (KeyCode::Esc, _) => return Some(TuiAction::Quit),
+            (KeyCode::Enter, _) if key.is_some() => return Some(TuiAction::Copy),
+            (KeyCode::Char('e'), true) if key.is_some() => return Some(TuiAction::Edit),
+            (KeyCode::Char('r'), true) => {
+                if let Some(key) = key {
+                    self.mode = TuiMode::Rename(key);
+                }
+            }
+            (KeyCode::Char('d'), true) => {
+                if let Some(key) = key {
+                    self.mode = TuiMode::Duplicate(format!("{}_copy", key));
+                }
+            }
+            (KeyCode::Char('x'), true) | (KeyCode::Delete, _) if key.is_some() => {
+                self.mode = TuiMode::ConfirmDelete;
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), true) => self.move_selection(false),
+            (KeyCode::Down, _) | (KeyCode::Char('n'), true) => self.move_selection(true),
+            (KeyCode::Tab, _) | (KeyCode::BackTab, _) if self.collections.len() > 1 => {
+                let count = self.collections.len();
+                self.current = match code {
+                    KeyCode::Tab => (self.current + 1) % count,
+                    _ => (self.current + count - 1) % count,
+                };
+                self.selected = 0;
+                return Some(TuiAction::Reload);
+            }
+            (KeyCode::Char(c), false) => {
+                self.query.push(c);
+                self.selected = 0;
+                self.filter();
+            }
+            (KeyCode::Backspace, _) => {
+                self.query.pop();
+                self.filter();
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_filter_and_modes() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("rust.json");
+        fs::write(
+            &path,
+            r##"{
+  "hello": { "prefix": "hi", "body": ["println!(\"hello\");"], "description": "Say hello" },
+  "test": { "prefix": "tst", "body": ["#[test]", "fn ${1:name}() {}"], "description": "Test" }
+}"##,
+        )?;
+        let collection = Collection::from_path(path.to_str().unwrap().to_string());
+        let mut state = TuiState::new(
+            vec![TuiCollection {
+                name: "rust".to_string(),
+                sources: vec![SnippetSource::user(&collection.path)],
+                collection,
+            }],
+            0,
+        );
+        assert_eq!(state.matches, vec![0, 1]);
+
+        // The body is searched too
+        for c in "print".chars() {
+            state.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(state.selected_entry().unwrap().key, "hello");
+        assert_eq!(state.matches.len(), 1);
+
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(state.handle_key(ctrl('d')), None);
+        assert_eq!(state.mode, TuiMode::Duplicate("hello_copy".to_string()));
+        state.handle_key(KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(
+            state.handle_key(KeyEvent::from(KeyCode::Enter)),
+            Some(TuiAction::Duplicate("hello_cop".to_string()))
+        );
+        assert_eq!(state.mode, TuiMode::Filter);
+
+        // Anything but `y` cancels a delete
+        state.handle_key(ctrl('x'));
+        assert_eq!(state.handle_key(KeyEvent::from(KeyCode::Char('n'))), None);
+        state.handle_key(ctrl('x'));
+        assert_eq!(
+            state.handle_key(KeyEvent::from(KeyCode::Char('y'))),
+            Some(TuiAction::Delete)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+pub mod apply_transform;
+pub mod parse_query;
+pub mod parse_snippet_body;
+pub mod print_snippet_ast;
+pub mod query_ast;
+pub mod render_snippet_ast;
+pub mod snippet_ast;
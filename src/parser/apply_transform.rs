@@ -0,0 +1,157 @@
+use crate::parser::snippet_ast::{FormatItem, FormatKind, Transform};
+use regex::{Captures, RegexBuilder};
+
+/// Applies `/regex/format/flags` to a value, like VSCode does.
+///
+/// Only the first match is replaced unless the `g` flag is given; `i` makes the regex case
+/// insensitive and `m` makes `^` and `$` match at line ends. A regex that doesn't compile
+/// leaves the value unchanged.
+pub fn apply_transform(value: &str, transform: &Transform) -> String {
+    let regex = match RegexBuilder::new(&transform.pattern())
+        .case_insensitive(transform.flags.contains('i'))
+        .multi_line(transform.flags.contains('m'))
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(error) => {
+            log::warn!("Ignoring transform /{}/: {}", transform.regex, error);
+            return value.to_string();
+        }
+    };
+    let replace = |captures: &Captures| format(&transform.format, captures);
+    if transform.flags.contains('g') {
+        regex.replace_all(value, replace).into_owned()
+    } else {
+        regex.replace(value, replace).into_owned()
+    }
+}
+
+fn format(items: &[FormatItem], captures: &Captures) -> String {
+    let mut out = String::new();
+    for item in items {
+        match item {
+            FormatItem::Text(text) => out.push_str(&text.value),
+            FormatItem::Group { index, kind, .. } => {
+                let group = captures
+                    .get(*index as usize)
+                    .map_or("", |group| group.as_str());
+                // Like VSCode, a group that didn't match or matched nothing counts as unset
+                let value = match kind {
+                    FormatKind::Plain => group.to_string(),
+                    FormatKind::Case(case) => change_case(group, case),
+                    FormatKind::If(text) if !group.is_empty() => text.value.clone(),
+                    FormatKind::IfElse(text, _) if !group.is_empty() => text.value.clone(),
+                    FormatKind::IfElse(_, text) | FormatKind::Else { text, .. }
+                        if group.is_empty() =>
+                    {
+                        text.value.clone()
+                    }
+                    _ => group.to_string(),
+                };
+                out.push_str(&value);
+            }
+        }
+    }
+    out
+}
+
+fn change_case(value: &str, case: &str) -> String {
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    };
+    // Words are runs of ASCII letters and digits, as in VSCode
+    let words = || {
+        value
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+    };
+    match case {
+        "upcase" => value.to_uppercase(),
+        "downcase" => value.to_lowercase(),
+        "capitalize" => capitalize(value),
+        "pascalcase" => words().map(capitalize).collect(),
+        "camelcase" => words()
+            .enumerate()
+            .map(|(index, word)| match index {
+                0 => {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_lowercase().chain(chars).collect())
+                        .unwrap_or_default()
+                }
+                _ => capitalize(word),
+            })
+            .collect(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_snippet_body::parse_snippet_body;
+    use crate::parser::snippet_ast::Node;
+
+    fn transform(value: &str, body: &str) -> String {
+        match parse_snippet_body(body).nodes.as_slice() {
+            [Node::Variable {
+                transform: Some(transform),
+                ..
+            }] => apply_transform(value, transform),
+            nodes => panic!("expected a variable with a transform, got {:?}", nodes),
+        }
+    }
+
+    #[test]
+    fn test_apply_transform_documented_examples() {
+        // Examples from the Variable transforms section of the VSCode snippet docs
+        assert_eq!(
+            transform("example-123.456-TEST.js", "${TM_FILENAME/[\\.]/_/}"),
+            "example-123_456-TEST.js"
+        );
+        assert_eq!(
+            transform("example-123.456-TEST.js", "${TM_FILENAME/[\\.-]/_/g}"),
+            "example_123_456_TEST_js"
+        );
+        assert_eq!(
+            transform(
+                "example-123.456-TEST.js",
+                "${TM_FILENAME/(.*)/${1:/upcase}/}"
+            ),
+            "EXAMPLE-123.456-TEST.JS"
+        );
+        assert_eq!(
+            transform("example-123.456-TEST.js", "${TM_FILENAME/[^0-9^a-z]//gi}"),
+            "example123456TESTjs"
+        );
+        assert_eq!(
+            transform("my_file", "${TM_FILENAME_BASE/(.*)/${1:/pascalcase}/}"),
+            "MyFile"
+        );
+        assert_eq!(transform("bar-foo", "${V/(.*)/${1:/camelcase}/}"), "barFoo");
+        assert_eq!(
+            transform("bar-foo", "${V/(.*)/${1:/capitalize}/}"),
+            "Bar-foo"
+        );
+        assert_eq!(transform("FOO", "${V/(.*)/${1:/downcase}/}"), "foo");
+    }
+
+    #[test]
+    fn test_apply_transform_conditionals() {
+        assert_eq!(transform("x", "${V/(.*)/${1:?yes:no}/}"), "yes");
+        assert_eq!(transform("", "${V/(.*)/${1:?yes:no}/}"), "no");
+        assert_eq!(transform("a", "${V/(a)|b/${1:+one}/}"), "one");
+        assert_eq!(transform("b", "${V/(a)|b/${1:+one}/}"), "");
+        assert_eq!(transform("b", "${V/(a)|b/${1:-none}/}"), "none");
+        assert_eq!(transform("b", "${V/(a)|b/${1:none}/}"), "none");
+        assert_eq!(transform("a", "${V/(a)|b/${1:-none}/}"), "a");
+        // Multi-line anchors and invalid regexes
+        assert_eq!(transform("a\nb", "${V/^/> /gm}"), "> a\n> b");
+        assert_eq!(transform("a(b", "${V/(/x/}"), "a(b");
+    }
+}
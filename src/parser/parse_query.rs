@@ -0,0 +1,321 @@
+use crate::parser::query_ast::{Query, QueryField, QueryPattern};
+use regex::Regex;
+
+/// Parses a `snip search` query.
+///
+/// Terms next to each other must all match, `OR` between them lets either side match and
+/// binds looser, `-` negates a term and parentheses group terms. A term is a
+/// word, a `"quoted phrase"` or a `/regex/` with `i`, `m`, `s` or `x` flags, optionally
+/// limited to a field with `key:`, `prefix:`, `desc:`, `body:`, `tag:` or `scope:`.
+pub fn parse_query(query: &str) -> anyhow::Result<Query> {
+    let tokens = lex(query)?;
+    let mut parser = Parser {
+        query,
+        tokens,
+        pos: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Err(anyhow::anyhow!("The query is empty"));
+    }
+    let parsed = parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        Some((_, offset)) => Err(parser.error("Unmatched ')'", *offset)),
+        None => Ok(parsed),
+    }
+}
+
+#[derive(Debug)]
+enum Token {
+    Open,
+    Close,
+    Or,
+    Not,
+    Term(Option<QueryField>, QueryPattern),
+}
+
+/// 1-based column of the byte `offset`, for errors.
+fn column(query: &str, offset: usize) -> usize {
+    query[..offset].chars().count() + 1
+}
+
+fn lex(query: &str) -> anyhow::Result<Vec<(Token, usize)>> {
+    let error = |message: String, offset: usize| {
+        anyhow::anyhow!("{} at column {}", message, column(query, offset))
+    };
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' => {
+                chars.next();
+                let token = if c == '(' { Token::Open } else { Token::Close };
+                tokens.push((token, start));
+                continue;
+            }
+            '-' if query[start + 1..]
+                .chars()
+                .next()
+                .is_some_and(|next| !next.is_whitespace()) =>
+            {
+                chars.next();
+                tokens.push((Token::Not, start));
+                continue;
+            }
+            _ => {}
+        }
+
+        // `field:` is letters followed by a colon
+        let name_len = query[start..]
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(query.len() - start);
+        let mut field = None;
+        if name_len > 0 && query[start + name_len..].starts_with(':') {
+            let name = &query[start..start + name_len];
+            field = Some(QueryField::from_name(name).ok_or_else(|| {
+                let names: Vec<&str> = QueryField::ALL.iter().map(|f| f.as_str()).collect();
+                error(
+                    format!(
+                        "Unknown field '{}', expected one of {} or a quoted \"{}:...\"",
+                        name,
+                        names.join(", "),
+                        name
+                    ),
+                    start,
+                )
+            })?);
+            while chars
+                .peek()
+                .is_some_and(|&(offset, _)| offset <= start + name_len)
+            {
+                chars.next();
+            }
+        }
+
+        let value_start = chars.peek().map_or(query.len(), |&(offset, _)| offset);
+        let next = chars.peek().map(|&(_, c)| c);
+        let pattern = match next.filter(|&c| !c.is_whitespace() && c != '(' && c != ')') {
+            // Only reached after a field, a term starts with any other char
+            None => {
+                let name = field.map_or("", |field| field.as_str());
+                return Err(error(format!("Missing a value after '{}:'", name), start));
+            }
+            Some('"') => {
+                chars.next();
+                let mut phrase = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) if chars.peek().is_some_and(|&(_, c)| c == '"') => {
+                            phrase.push('"');
+                            chars.next();
+                        }
+                        Some((_, c)) => phrase.push(c),
+                        None => return Err(error("Unterminated quote".to_string(), value_start)),
+                    }
+                }
+                QueryPattern::Text(phrase)
+            }
+            Some('/') => {
+                chars.next();
+                let mut pattern = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) => break,
+                        Some((_, '\\')) if chars.peek().is_some_and(|&(_, c)| c == '/') => {
+                            pattern.push('/');
+                            chars.next();
+                        }
+                        Some((_, c)) => pattern.push(c),
+                        None => return Err(error("Unterminated regex".to_string(), value_start)),
+                    }
+                }
+                let mut flags = String::new();
+                while let Some(&(offset, c)) = chars.peek().filter(|(_, c)| c.is_alphanumeric()) {
+                    if !"imsx".contains(c) {
+                        return Err(error(format!("Unknown regex flag '{}'", c), offset));
+                    }
+                    flags.push(c);
+                    chars.next();
+                }
+                if !flags.is_empty() {
+                    pattern = format!("(?{}){}", flags, pattern);
+                }
+                let regex = Regex::new(&pattern)
+                    .map_err(|e| error(format!("Invalid regex: {}", e), value_start))?;
+                QueryPattern::Regex(regex)
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars
+                    .peek()
+                    .filter(|(_, c)| !c.is_whitespace() && *c != '(' && *c != ')')
+                {
+                    word.push(c);
+                    chars.next();
+                }
+                if word == "OR" && field.is_none() {
+                    tokens.push((Token::Or, start));
+                    continue;
+                }
+                QueryPattern::Text(word)
+            }
+        };
+        tokens.push((Token::Term(field, pattern), start));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str, offset: usize) -> anyhow::Error {
+        anyhow::anyhow!("{} at column {}", message, column(self.query, offset))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    /// Offset of the next token, or the end of the query.
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.query.len(), |(_, offset)| *offset)
+    }
+
+    fn or(&mut self) -> anyhow::Result<Query> {
+        let mut queries = vec![self.and()?];
+        while let Some(Token::Or) = self.peek() {
+            self.pos += 1;
+            queries.push(self.and()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.pop().unwrap(),
+            _ => Query::Or(queries),
+        })
+    }
+
+    fn and(&mut self) -> anyhow::Result<Query> {
+        let mut queries = Vec::new();
+        while !matches!(self.peek(), None | Some(Token::Or) | Some(Token::Close)) {
+            queries.push(self.unary()?);
+        }
+        if queries.is_empty() {
+            let after_or = self.pos > 0 && matches!(self.tokens[self.pos - 1].0, Token::Or);
+            let message = match (self.peek(), after_or) {
+                (Some(Token::Or), _) => "Expected a term before OR",
+                (_, true) => "Expected a term after OR",
+                (Some(Token::Close), _) => "Expected a term before ')'",
+                _ => "Expected a term",
+            };
+            return Err(self.error(message, self.offset()));
+        }
+        Ok(match queries.len() {
+            1 => queries.pop().unwrap(),
+            _ => Query::And(queries),
+        })
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Query> {
+        let offset = self.offset();
+        self.pos += 1;
+        match &self.tokens[self.pos - 1].0 {
+            Token::Not => match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => {
+                    Err(self.error("Expected a term after '-'", offset))
+                }
+                _ => Ok(Query::Not(Box::new(self.unary()?))),
+            },
+            Token::Open => {
+                let query = self.or()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(query)
+                    }
+                    _ => Err(self.error("Missing ')' for the '('", offset)),
+                }
+            }
+            Token::Term(field, pattern) => Ok(Query::Term {
+                field: *field,
+                pattern: pattern.clone(),
+            }),
+            Token::Or | Token::Close => unreachable!("`and` stops at OR and ')'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::snippet_model::Snippet;
+
+    fn snippet(json: &str) -> Snippet {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_query_matches() -> anyhow::Result<()> {
+        let deref = snippet(
+            r#"{ "prefix": "impl_deref", "body": ["impl Deref for $1 {", "}"], "description": "Implement Deref", "tags": ["trait"] }"#,
+        );
+        let clone = snippet(
+            r#"{ "prefix": ["impl_clone", "ic"], "body": "impl Clone for $1 {}", "scope": "rust" }"#,
+        );
+        let matching = |query: &str| -> anyhow::Result<Vec<&str>> {
+            let query = parse_query(query)?;
+            Ok([("deref", &deref), ("clone", &clone)]
+                .into_iter()
+                .filter(|(key, snippet)| query.matches(key, snippet))
+                .map(|(key, _)| key)
+                .collect())
+        };
+
+        assert_eq!(matching(r"prefix:/^impl/ body:Deref")?, vec!["deref"]);
+        assert_eq!(matching("impl -deref")?, vec!["clone"]);
+        assert_eq!(matching("tag:trait OR scope:rust")?, vec!["deref", "clone"]);
+        assert_eq!(
+            matching("prefix:ic (desc:implement OR key:clone)")?,
+            vec!["clone"]
+        );
+        assert_eq!(matching(r#"body:"for $1 {}""#)?, vec!["clone"]);
+        // Smart-case: lowercase ignores case, uppercase must match it
+        assert_eq!(matching("deref")?, vec!["deref"]);
+        assert_eq!(matching("body:deref")?, vec!["deref"]);
+        assert_eq!(matching("key:Deref")?, Vec::<&str>::new());
+        assert_eq!(matching("/DEREF/i")?, vec!["deref"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_query_errors() {
+        let error = |query: &str| parse_query(query).unwrap_err().to_string();
+
+        assert_eq!(error("  "), "The query is empty");
+        assert_eq!(
+            error("name:x"),
+            "Unknown field 'name', expected one of key, prefix, desc, body, tag, scope or a quoted \"name:...\" at column 1"
+        );
+        assert_eq!(
+            error("impl key:"),
+            "Missing a value after 'key:' at column 6"
+        );
+        assert_eq!(error("body:\"impl"), "Unterminated quote at column 6");
+        assert_eq!(error("/impl"), "Unterminated regex at column 1");
+        assert_eq!(error("/impl/g"), "Unknown regex flag 'g' at column 7");
+        assert!(error("/(impl/").starts_with("Invalid regex: "));
+        assert_eq!(error("OR impl"), "Expected a term before OR at column 1");
+        assert_eq!(error("impl OR"), "Expected a term after OR at column 8");
+        assert_eq!(error("(impl"), "Missing ')' for the '(' at column 1");
+        assert_eq!(error("impl)"), "Unmatched ')' at column 5");
+        assert_eq!(error("impl -)"), "Expected a term after '-' at column 6");
+    }
+}
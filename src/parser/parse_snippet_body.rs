@@ -0,0 +1,541 @@
+use crate::parser::snippet_ast::{FormatItem, FormatKind, Node, SnippetAst, Text, Transform};
+use std::collections::HashSet;
+
+/// Deeper placeholders are kept as text rather than risking the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Parses a snippet body with the TextMate/VSCode snippet grammar.
+///
+/// Like VSCode, parsing never fails: anything that isn't a valid tabstop, placeholder, choice
+/// or variable is kept as text.
+pub fn parse_snippet_body(body: &str) -> SnippetAst {
+    let mut parser = Parser {
+        body,
+        pos: 0,
+        depth: 0,
+        failed: HashSet::new(),
+    };
+    SnippetAst {
+        nodes: parser.nodes(false),
+    }
+}
+
+struct Parser<'a> {
+    body: &'a str,
+    pos: usize,
+    depth: usize,
+    /// Positions of `$` known not to start a construct. An unclosed placeholder is retried as
+    /// text, so without this, nested unclosed ones would be parsed again and again.
+    failed: HashSet<usize>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.body[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.body[self.pos..].chars().nth(offset)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Nodes up to the end of the body, or up to an unescaped `}` when `nested`.
+    fn nodes(&mut self, nested: bool) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut text = Text::default();
+        let mut text_start = self.pos;
+
+        while let Some(c) = self.peek() {
+            if nested && c == '}' {
+                break;
+            }
+            if c == '$' {
+                let start = self.pos;
+                if let Some(node) = self.dollar() {
+                    if !text.raw.is_empty() {
+                        nodes.push(Node::Text {
+                            text: std::mem::take(&mut text),
+                            span: text_start..start,
+                        });
+                    }
+                    nodes.push(node);
+                    text_start = self.pos;
+                    continue;
+                }
+                // Not a valid construct, the `$` is literal text
+                self.pos = start + 1;
+                text.raw.push('$');
+                text.value.push('$');
+                continue;
+            }
+            self.text_char(&mut text, &['$', '}', '\\']);
+        }
+
+        if !text.raw.is_empty() {
+            nodes.push(Node::Text {
+                text,
+                span: text_start..self.pos,
+            });
+        }
+        nodes
+    }
+
+    /// Reads one character of text, resolving `\` followed by one of `escapable`.
+    fn text_char(&mut self, text: &mut Text, escapable: &[char]) {
+        let c = self.peek().expect("not at the end of the body");
+        self.pos += c.len_utf8();
+        text.raw.push(c);
+        if c == '\\' {
+            if let Some(next) = self.peek().filter(|next| escapable.contains(next)) {
+                self.pos += next.len_utf8();
+                text.raw.push(next);
+                text.value.push(next);
+                return;
+            }
+        }
+        text.value.push(c);
+    }
+
+    /// A construct starting at `$`, leaving the position untouched when there is none.
+    fn dollar(&mut self) -> Option<Node> {
+        let start = self.pos;
+        if self.failed.contains(&start) {
+            return None;
+        }
+        if self.depth >= MAX_DEPTH {
+            self.failed.insert(start);
+            return None;
+        }
+        self.depth += 1;
+        let node = self.construct(start);
+        self.depth -= 1;
+        if node.is_none() {
+            self.failed.insert(start);
+            self.pos = start;
+        }
+        node
+    }
+
+    fn construct(&mut self, start: usize) -> Option<Node> {
+        self.eat('$');
+        if let Some((index, leading_zeros)) = self.index() {
+            return Some(Node::Tabstop {
+                index,
+                leading_zeros,
+                braced: false,
+                transform: None,
+                span: start..self.pos,
+            });
+        }
+        if let Some(name) = self.name() {
+            return Some(Node::Variable {
+                name,
+                braced: false,
+                default: None,
+                transform: None,
+                span: start..self.pos,
+            });
+        }
+        if !self.eat('{') {
+            return None;
+        }
+
+        if let Some((index, leading_zeros)) = self.index() {
+            let node = match self.peek()? {
+                '}' => Node::Tabstop {
+                    index,
+                    leading_zeros,
+                    braced: true,
+                    transform: None,
+                    span: 0..0,
+                },
+                ':' => {
+                    self.eat(':');
+                    let children = self.nodes(true);
+                    Node::Placeholder {
+                        index,
+                        leading_zeros,
+                        children,
+                        span: 0..0,
+                    }
+                }
+                '|' => {
+                    self.eat('|');
+                    let options = self.choice_options()?;
+                    Node::Choice {
+                        index,
+                        leading_zeros,
+                        options,
+                        span: 0..0,
+                    }
+                }
+                '/' => Node::Tabstop {
+                    index,
+                    leading_zeros,
+                    braced: true,
+                    transform: Some(self.transform()?),
+                    span: 0..0,
+                },
+                _ => return None,
+            };
+            if !self.eat('}') {
+                return None;
+            }
+            return Some(with_span(node, start..self.pos));
+        }
+
+        let name = self.name()?;
+        let (default, transform) = match self.peek()? {
+            '}' => (None, None),
+            ':' => {
+                self.eat(':');
+                (Some(self.nodes(true)), None)
+            }
+            '/' => (None, Some(self.transform()?)),
+            _ => return None,
+        };
+        if !self.eat('}') {
+            return None;
+        }
+        Some(Node::Variable {
+            name,
+            braced: true,
+            default,
+            transform,
+            span: start..self.pos,
+        })
+    }
+
+    /// The index and the `0`s written before it.
+    fn index(&mut self) -> Option<(u32, usize)> {
+        let digits = &self.body[self.pos..];
+        let digits = &digits[..digits.len()
+            - digits
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len()];
+        let index: u32 = digits.parse().ok()?;
+        self.pos += digits.len();
+        Some((index, digits.len() - index.to_string().len()))
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let rest = &self.body[self.pos..];
+        if !rest.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let len = rest
+            .chars()
+            .take_while(|c| *c == '_' || c.is_ascii_alphanumeric())
+            .count();
+        self.pos += len;
+        Some(rest[..len].to_string())
+    }
+
+    /// `one,two|` up to and including the closing `|`, which must be followed by `}`.
+    fn choice_options(&mut self) -> Option<Vec<Text>> {
+        let mut options = Vec::new();
+        let mut option = Text::default();
+        loop {
+            match self.peek()? {
+                ',' => {
+                    self.eat(',');
+                    options.push(std::mem::take(&mut option));
+                }
+                '|' if self.peek_at(1) == Some('}') => {
+                    self.eat('|');
+                    options.push(option);
+                    return Some(options);
+                }
+                '|' => return None,
+                _ => self.text_char(&mut option, &['$', '}', '\\', ',', '|']),
+            }
+        }
+    }
+
+    /// `/regex/format/flags`, starting at the first `/`.
+    fn transform(&mut self) -> Option<Transform> {
+        let start = self.pos;
+        self.eat('/');
+
+        let regex_start = self.pos;
+        loop {
+            match self.peek()? {
+                '/' => break,
+                '\\' => {
+                    self.eat('\\');
+                    let next = self.peek()?;
+                    self.pos += next.len_utf8();
+                }
+                c => self.pos += c.len_utf8(),
+            }
+        }
+        let regex = self.body[regex_start..self.pos].to_string();
+        self.eat('/');
+
+        let mut format = Vec::new();
+        let mut text = Text::default();
+        loop {
+            match self.peek()? {
+                '/' => break,
+                '$' => {
+                    let item_start = self.pos;
+                    match self.format_group() {
+                        Some(item) => {
+                            if !text.raw.is_empty() {
+                                format.push(FormatItem::Text(std::mem::take(&mut text)));
+                            }
+                            format.push(item);
+                        }
+                        None => {
+                            self.pos = item_start + 1;
+                            text.raw.push('$');
+                            text.value.push('$');
+                        }
+                    }
+                }
+                _ => self.text_char(&mut text, &['$', '/', '\\']),
+            }
+        }
+        if !text.raw.is_empty() {
+            format.push(FormatItem::Text(text));
+        }
+        self.eat('/');
+
+        let flags_len = self.body[self.pos..]
+            .chars()
+            .take_while(char::is_ascii_alphabetic)
+            .count();
+        let flags = self.body[self.pos..self.pos + flags_len].to_string();
+        self.pos += flags_len;
+
+        Some(Transform {
+            regex,
+            format,
+            flags,
+            span: start..self.pos,
+        })
+    }
+
+    /// `$1`, `${1}` or `${1:...}` inside a transform format.
+    fn format_group(&mut self) -> Option<FormatItem> {
+        self.eat('$');
+        if let Some((index, leading_zeros)) = self.index() {
+            return Some(FormatItem::Group {
+                index,
+                leading_zeros,
+                braced: false,
+                kind: FormatKind::Plain,
+            });
+        }
+        if !self.eat('{') {
+            return None;
+        }
+        let (index, leading_zeros) = self.index()?;
+        let kind = if self.eat(':') {
+            match self.peek()? {
+                '/' => {
+                    self.eat('/');
+                    let name = self.name()?;
+                    FormatKind::Case(name)
+                }
+                '+' => {
+                    self.eat('+');
+                    FormatKind::If(self.format_text(&['}']))
+                }
+                '?' => {
+                    self.eat('?');
+                    let if_text = self.format_text(&[':']);
+                    if !self.eat(':') {
+                        return None;
+                    }
+                    FormatKind::IfElse(if_text, self.format_text(&['}']))
+                }
+                '-' => {
+                    self.eat('-');
+                    FormatKind::Else {
+                        text: self.format_text(&['}']),
+                        dash: true,
+                    }
+                }
+                _ => FormatKind::Else {
+                    text: self.format_text(&['}']),
+                    dash: false,
+                },
+            }
+        } else {
+            FormatKind::Plain
+        };
+        if !self.eat('}') {
+            return None;
+        }
+        Some(FormatItem::Group {
+            index,
+            leading_zeros,
+            braced: true,
+            kind,
+        })
+    }
+
+    /// Text of a conditional format branch, up to one of the unescaped `stops`.
+    fn format_text(&mut self, stops: &[char]) -> Text {
+        let mut text = Text::default();
+        while let Some(c) = self.peek() {
+            if stops.contains(&c) {
+                break;
+            }
+            self.text_char(&mut text, &['$', '}', '\\', ':', '/']);
+        }
+        text
+    }
+}
+
+fn with_span(mut node: Node, span: std::ops::Range<usize>) -> Node {
+    if let Node::Tabstop { span: old, .. }
+    | Node::Placeholder { span: old, .. }
+    | Node::Choice { span: old, .. } = &mut node
+    {
+        *old = span;
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(raw: &str) -> Text {
+        Text {
+            raw: raw.to_string(),
+            value: raw.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_tabstops_placeholders_and_choices() {
+        let ast = parse_snippet_body("fn ${1:name}(${2|a,b|}) {\n\t$0\n}");
+
+        assert_eq!(
+            ast.nodes,
+            vec![
+                Node::Text {
+                    text: text("fn "),
+                    span: 0..3
+                },
+                Node::Placeholder {
+                    index: 1,
+                    leading_zeros: 0,
+                    children: vec![Node::Text {
+                        text: text("name"),
+                        span: 7..11
+                    }],
+                    span: 3..12
+                },
+                Node::Text {
+                    text: text("("),
+                    span: 12..13
+                },
+                Node::Choice {
+                    index: 2,
+                    leading_zeros: 0,
+                    options: vec![text("a"), text("b")],
+                    span: 13..22
+                },
+                Node::Text {
+                    text: text(") {\n\t"),
+                    span: 22..27
+                },
+                Node::Tabstop {
+                    index: 0,
+                    leading_zeros: 0,
+                    braced: false,
+                    transform: None,
+                    span: 27..29
+                },
+                Node::Text {
+                    text: text("\n}"),
+                    span: 29..31
+                },
+            ]
+        );
+        assert_eq!(ast.tabstops(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_parse_nested_placeholders_and_variables() {
+        let ast = parse_snippet_body("${1:Hello ${2:$TM_FILENAME}} ${USER:nobody}");
+
+        assert_eq!(ast.tabstops(), vec![1, 2]);
+        assert_eq!(ast.placeholders().len(), 2);
+        assert_eq!(ast.variables(), vec!["TM_FILENAME", "USER"]);
+        let Node::Variable { default, .. } = &ast.nodes[2] else {
+            panic!("expected a variable");
+        };
+        assert_eq!(
+            default.as_deref(),
+            Some(
+                &[Node::Text {
+                    text: text("nobody"),
+                    span: 36..42
+                }][..]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_transforms() {
+        let ast = parse_snippet_body("${TM_FILENAME/(.*)\\.rs/${1:/upcase}_${1:?yes:no}/g}");
+
+        let Node::Variable {
+            transform: Some(transform),
+            ..
+        } = &ast.nodes[0]
+        else {
+            panic!("expected a variable with a transform");
+        };
+        assert_eq!(transform.regex, "(.*)\\.rs");
+        assert_eq!(transform.flags, "g");
+        assert_eq!(
+            transform.format,
+            vec![
+                FormatItem::Group {
+                    index: 1,
+                    leading_zeros: 0,
+                    braced: true,
+                    kind: FormatKind::Case("upcase".to_string())
+                },
+                FormatItem::Text(text("_")),
+                FormatItem::Group {
+                    index: 1,
+                    leading_zeros: 0,
+                    braced: true,
+                    kind: FormatKind::IfElse(text("yes"), text("no"))
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_syntax_is_text() {
+        let ast = parse_snippet_body("cost: $ 5, ${1:open, \\$HOME, }");
+
+        assert_eq!(ast.tabstops(), vec![1]);
+        assert!(matches!(&ast.nodes[0], Node::Text { text, .. } if text.raw == "cost: $ 5, "));
+
+        let ast = parse_snippet_body("${1:never closed");
+        assert_eq!(ast.tabstops(), Vec::<u32>::new());
+        assert_eq!(ast.nodes.len(), 1);
+
+        // Unclosed nesting is kept as text without blowing up
+        let body = "${1:".repeat(10_000);
+        let ast = parse_snippet_body(&body);
+        assert!(matches!(&ast.nodes[..], [Node::Text { text, .. }] if text.raw == body));
+    }
+}
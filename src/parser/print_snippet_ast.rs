@@ -0,0 +1,156 @@
+use crate::parser::snippet_ast::{FormatItem, FormatKind, Node, SnippetAst, Transform};
+
+/// Turns an AST back into snippet text. Printing a parsed body gives back the same body.
+pub fn print_snippet_ast(ast: &SnippetAst) -> String {
+    let mut out = String::new();
+    print_nodes(&ast.nodes, &mut out);
+    out
+}
+
+fn print_nodes(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        print_node(node, out);
+    }
+}
+
+fn print_node(node: &Node, out: &mut String) {
+    match node {
+        Node::Text { text, .. } => out.push_str(&text.raw),
+        Node::Tabstop {
+            index,
+            leading_zeros,
+            braced: false,
+            ..
+        } => out.push_str(&format!("${}", index_text(*index, *leading_zeros))),
+        Node::Tabstop {
+            index,
+            leading_zeros,
+            transform,
+            ..
+        } => {
+            out.push_str(&format!("${{{}", index_text(*index, *leading_zeros)));
+            if let Some(transform) = transform {
+                print_transform(transform, out);
+            }
+            out.push('}');
+        }
+        Node::Placeholder {
+            index,
+            leading_zeros,
+            children,
+            ..
+        } => {
+            out.push_str(&format!("${{{}:", index_text(*index, *leading_zeros)));
+            print_nodes(children, out);
+            out.push('}');
+        }
+        Node::Choice {
+            index,
+            leading_zeros,
+            options,
+            ..
+        } => {
+            let options: Vec<&str> = options.iter().map(|option| option.raw.as_str()).collect();
+            let index = index_text(*index, *leading_zeros);
+            out.push_str(&format!("${{{}|{}|}}", index, options.join(",")));
+        }
+        Node::Variable {
+            name,
+            braced: false,
+            ..
+        } => out.push_str(&format!("${}", name)),
+        Node::Variable {
+            name,
+            default,
+            transform,
+            ..
+        } => {
+            out.push_str(&format!("${{{}", name));
+            if let Some(default) = default {
+                out.push(':');
+                print_nodes(default, out);
+            }
+            if let Some(transform) = transform {
+                print_transform(transform, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn print_transform(transform: &Transform, out: &mut String) {
+    out.push('/');
+    out.push_str(&transform.regex);
+    out.push('/');
+    for item in &transform.format {
+        match item {
+            FormatItem::Text(text) => out.push_str(&text.raw),
+            FormatItem::Group {
+                index,
+                leading_zeros,
+                braced: false,
+                ..
+            } => out.push_str(&format!("${}", index_text(*index, *leading_zeros))),
+            FormatItem::Group {
+                index,
+                leading_zeros,
+                kind,
+                ..
+            } => {
+                out.push_str(&format!("${{{}", index_text(*index, *leading_zeros)));
+                match kind {
+                    FormatKind::Plain => {}
+                    FormatKind::Case(case) => out.push_str(&format!(":/{}", case)),
+                    FormatKind::If(text) => out.push_str(&format!(":+{}", text.raw)),
+                    FormatKind::IfElse(if_text, else_text) => {
+                        out.push_str(&format!(":?{}:{}", if_text.raw, else_text.raw))
+                    }
+                    FormatKind::Else { text, dash: true } => {
+                        out.push_str(&format!(":-{}", text.raw))
+                    }
+                    FormatKind::Else { text, dash: false } => {
+                        out.push_str(&format!(":{}", text.raw))
+                    }
+                }
+                out.push('}');
+            }
+        }
+    }
+    out.push('/');
+    out.push_str(&transform.flags);
+}
+
+/// The index as written, e.g. `01`.
+fn index_text(index: u32, leading_zeros: usize) -> String {
+    format!("{}{}", "0".repeat(leading_zeros), index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_snippet_body::parse_snippet_body;
+
+    #[test]
+    fn test_print_is_lossless() {
+        let bodies = [
+            "fn ${1:name}(${2|a,b\\,c|}) {\n\t$0\n}",
+            "${1:Hello ${2:$TM_FILENAME}} ${USER:nobody} ${USER:} ${1}",
+            "${TM_FILENAME/(.*)\\.rs/${1:/upcase}_$1_${1:+if}${1:?yes:no}${1:-else}${1:else}${1}/gi}",
+            "${2/^(.)\\/x$/\\$1 ${1:?\\:}/}",
+            "literal \\$1 \\} \\\\ $ ${ ${1 ${1:open } } ${1|bad|",
+            "${1:${1:${1:",
+            "unicode ${1:é} $ü ✓",
+            "$01 $00 ${01:x} ${002|a|} ${01/(a)/$01/}",
+            "",
+        ];
+
+        for body in bodies {
+            assert_eq!(print_snippet_ast(&parse_snippet_body(body)), body);
+        }
+
+        // Leading zeros are printed back, the index itself is still a number like in VSCode
+        let ast = parse_snippet_body("$01 ${00:x}");
+        assert_eq!(ast.tabstops(), vec![1, 0]);
+        assert_eq!(print_snippet_ast(&ast), "$01 ${00:x}");
+    }
+}
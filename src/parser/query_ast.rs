@@ -0,0 +1,111 @@
+use crate::models::snippet_model::Snippet;
+use regex::Regex;
+
+/// A `snip search` query, as parsed by `parse_query`.
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// Terms next to each other, which must all match
+    And(Vec<Query>),
+    /// `a OR b`
+    Or(Vec<Query>),
+    /// `-term`
+    Not(Box<Query>),
+    /// `field:pattern`, or a bare `pattern` matching any field
+    Term {
+        field: Option<QueryField>,
+        pattern: QueryPattern,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryField {
+    Key,
+    Prefix,
+    Desc,
+    Body,
+    Tag,
+    Scope,
+}
+
+#[derive(Debug, Clone)]
+pub enum QueryPattern {
+    /// A word or a quoted phrase, found anywhere in the field with smart-case
+    Text(String),
+    /// `/regex/flags`
+    Regex(Regex),
+}
+
+impl QueryField {
+    pub const ALL: [QueryField; 6] = [
+        QueryField::Key,
+        QueryField::Prefix,
+        QueryField::Desc,
+        QueryField::Body,
+        QueryField::Tag,
+        QueryField::Scope,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryField::Key => "key",
+            QueryField::Prefix => "prefix",
+            QueryField::Desc => "desc",
+            QueryField::Body => "body",
+            QueryField::Tag => "tag",
+            QueryField::Scope => "scope",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<QueryField> {
+        QueryField::ALL
+            .into_iter()
+            .find(|field| field.as_str() == name)
+    }
+
+    /// The values of the field, fields holding a list have one value per item.
+    fn values(&self, key: &str, snippet: &Snippet) -> Vec<String> {
+        match self {
+            QueryField::Key => vec![key.to_string()],
+            QueryField::Prefix => snippet.prefixes().map(str::to_string).collect(),
+            QueryField::Desc => vec![snippet.description().to_string()],
+            QueryField::Body => vec![snippet.body_text()],
+            QueryField::Tag => snippet.tags().into_iter().map(str::to_string).collect(),
+            QueryField::Scope => snippet.scopes().map(str::to_string).collect(),
+        }
+    }
+}
+
+impl QueryPattern {
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            // Smart-case: a pattern with an uppercase char must match case
+            QueryPattern::Text(text) if text.chars().any(char::is_uppercase) => {
+                value.contains(text.as_str())
+            }
+            QueryPattern::Text(text) => value.to_lowercase().contains(text.as_str()),
+            QueryPattern::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, key: &str, snippet: &Snippet) -> bool {
+        match self {
+            Query::And(queries) => queries.iter().all(|query| query.matches(key, snippet)),
+            Query::Or(queries) => queries.iter().any(|query| query.matches(key, snippet)),
+            Query::Not(query) => !query.matches(key, snippet),
+            Query::Term { field, pattern } => {
+                let fields = match field {
+                    Some(field) => std::slice::from_ref(field),
+                    None => &QueryField::ALL,
+                };
+                fields.iter().any(|field| {
+                    field
+                        .values(key, snippet)
+                        .iter()
+                        .any(|value| pattern.is_match(value))
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::helpers::resolve_variable::resolve_variable;
+use crate::models::expand_context_model::ExpandContext;
+use crate::models::expansion_model::Expansion;
+use crate::parser::apply_transform::apply_transform;
+use crate::parser::print_snippet_ast::print_snippet_ast;
+use crate::parser::snippet_ast::{Node, SnippetAst, Transform};
+use std::collections::HashMap;
+use std::env;
+
+/// Renders a snippet to its final text, the way an editor would before any typing.
+///
+/// Tabstops take their `--set` value, else the default of their first placeholder or the
+/// first option of their choice. A placeholder can also be set by its default text, e.g.
+/// `name=bar` for `${1:name}`. Variables take their `--set` value, else their resolved value,
+/// else their default; unknown variables without a default are written as their name, like
+/// VSCode does. `${env:NAME}` reads the environment variable `NAME`. Transforms such as
+/// `${1/(.*)/${1:/upcase}/}` apply to the value of their tabstop or variable.
+pub fn render_snippet_ast(ast: &SnippetAst, context: &ExpandContext) -> Expansion {
+    let mut definitions: HashMap<u32, &Node> = HashMap::new();
+    for node in ast.walk() {
+        if let Node::Placeholder { index, .. } | Node::Choice { index, .. } = node {
+            definitions.entry(*index).or_insert(node);
+        }
+    }
+    let mut renderer = Renderer {
+        context,
+        definitions,
+        rendering: Vec::new(),
+        cursor: None,
+    };
+    let mut text = String::new();
+    renderer.nodes(&ast.nodes, &mut text);
+    Expansion {
+        cursor: renderer.cursor.unwrap_or(text.len()),
+        text,
+    }
+}
+
+struct Renderer<'a> {
+    context: &'a ExpandContext,
+    /// The placeholder or choice defining each tabstop's default
+    definitions: HashMap<u32, &'a Node>,
+    /// Tabstops being rendered, so a placeholder nested in itself doesn't loop
+    rendering: Vec<u32>,
+    cursor: Option<usize>,
+}
+
+impl Renderer<'_> {
+    fn nodes(&mut self, nodes: &[Node], out: &mut String) {
+        for node in nodes {
+            self.node(node, out);
+        }
+    }
+
+    fn node(&mut self, node: &Node, out: &mut String) {
+        match node {
+            Node::Text { text, .. } => out.push_str(&text.value),
+            Node::Tabstop { index, .. }
+            | Node::Placeholder { index, .. }
+            | Node::Choice { index, .. } => {
+                if *index == 0 && self.cursor.is_none() {
+                    self.cursor = Some(out.len());
+                }
+                match node {
+                    // A placeholder nested in its own default falls back to its own default
+                    Node::Placeholder { children, .. } if self.rendering.contains(index) => {
+                        self.nodes(children, out)
+                    }
+                    Node::Tabstop {
+                        transform: Some(transform),
+                        ..
+                    } => {
+                        let value = self.tabstop(*index);
+                        out.push_str(&apply_transform(&value, transform));
+                    }
+                    _ => {
+                        let value = self.tabstop(*index);
+                        out.push_str(&value);
+                    }
+                }
+            }
+            Node::Variable {
+                name,
+                default,
+                transform,
+                ..
+            } => {
+                let value = self.variable(name, default.as_deref(), transform.as_ref());
+                out.push_str(&value);
+            }
+        }
+    }
+
+    fn tabstop(&mut self, index: u32) -> String {
+        if let Some(value) = self.context.values.get(&index.to_string()) {
+            return value.clone();
+        }
+        if self.rendering.contains(&index) {
+            return String::new();
+        }
+        match self.definitions.get(&index).copied() {
+            Some(Node::Placeholder { children, .. }) => {
+                let name = print_snippet_ast(&SnippetAst {
+                    nodes: children.clone(),
+                });
+                if let Some(value) = self.context.values.get(&name) {
+                    return value.clone();
+                }
+                self.rendering.push(index);
+                let mut value = String::new();
+                // The cursor belongs to the outer text, not to a default rendered for a mirror
+                let cursor = self.cursor;
+                self.nodes(children, &mut value);
+                self.cursor = cursor;
+                self.rendering.pop();
+                value
+            }
+            Some(Node::Choice { options, .. }) => options
+                .first()
+                .map(|option| option.value.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    fn variable(
+        &mut self,
+        name: &str,
+        default: Option<&[Node]>,
+        transform: Option<&Transform>,
+    ) -> String {
+        let transformed = |value: String| match transform {
+            Some(transform) => apply_transform(&value, transform),
+            None => value,
+        };
+        if let Some(value) = self.context.values.get(name) {
+            return transformed(value.clone());
+        }
+        if name == "env" {
+            if let Some([Node::Text { text, .. }]) = default {
+                return env::var(&text.value).unwrap_or_default();
+            }
+        }
+        let render_default = |renderer: &mut Self, default: &[Node]| {
+            let mut value = String::new();
+            renderer.nodes(default, &mut value);
+            value
+        };
+        match (resolve_variable(name, self.context), default) {
+            (Some(value), _) if !value.is_empty() => transformed(value),
+            (_, Some(default)) => render_default(self, default),
+            (Some(value), None) => transformed(value),
+            (None, None) => name.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_snippet_body::parse_snippet_body;
+    use std::path::PathBuf;
+
+    fn render(body: &str, context: &ExpandContext) -> Expansion {
+        render_snippet_ast(&parse_snippet_body(body), context)
+    }
+
+    #[test]
+    fn test_render_defaults_and_cursor() {
+        let expansion = render(
+            "fn ${1:name}(${2|self,&self|}) {\n\t$0\n}\n// $1",
+            &ExpandContext::default(),
+        );
+
+        assert_eq!(expansion.text, "fn name(self) {\n\t\n}\n// name");
+        assert_eq!(expansion.cursor, 17);
+    }
+
+    #[test]
+    fn test_render_set_values() {
+        let context = ExpandContext {
+            values: HashMap::from([
+                ("1".to_string(), "Foo".to_string()),
+                ("name".to_string(), "bar".to_string()),
+                ("AUTHOR".to_string(), "me".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let expansion = render("struct ${1:Type} { ${2:name}: $1 } $AUTHOR", &context);
+
+        assert_eq!(expansion.text, "struct Foo { bar: Foo } me");
+        assert_eq!(expansion.cursor, expansion.text.len());
+    }
+
+    #[test]
+    fn test_render_variables() {
+        let context = ExpandContext {
+            file: Some(PathBuf::from("/src/lib.rs")),
+            ..Default::default()
+        };
+        env::set_var("SNIP_RENDER_TEST", "from env");
+
+        let expansion = render(
+            "$TM_FILENAME ${TM_SELECTED_TEXT:none} ${env:SNIP_RENDER_TEST} $UNKNOWN ${1:${1:self}}",
+            &context,
+        );
+
+        assert_eq!(expansion.text, "lib.rs none from env UNKNOWN self");
+    }
+
+    #[test]
+    fn test_render_transforms() {
+        let context = ExpandContext {
+            file: Some(PathBuf::from("/src/user_service.rs")),
+            values: HashMap::from([("2".to_string(), "".to_string())]),
+            ..Default::default()
+        };
+
+        let expansion = render(
+            "struct ${TM_FILENAME_BASE/(.*)/${1:/pascalcase}/};\n${1:get_name} ${1/(.*)/${1:/camelcase}/} ${2/(.*)/${1:?yes:no}/}",
+            &context,
+        );
+
+        assert_eq!(expansion.text, "struct UserService;\nget_name getName no");
+    }
+}
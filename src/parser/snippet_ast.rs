@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+/// Byte range of a node in the parsed body.
+pub type Span = Range<usize>;
+
+/// A snippet body parsed with the TextMate/VSCode snippet grammar.
+///
+/// Nodes keep the text as written (escapes, `$1` vs `${1}`), so printing the AST gives back
+/// the original body.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SnippetAst {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Text {
+        text: Text,
+        span: Span,
+    },
+    /// `$1`, `${1}` or `${1/regex/format/flags}`
+    Tabstop {
+        index: u32,
+        /// `0`s written before the index, as in `$01`
+        leading_zeros: usize,
+        braced: bool,
+        transform: Option<Transform>,
+        span: Span,
+    },
+    /// `${1:default}`, the default may hold other nodes
+    Placeholder {
+        index: u32,
+        /// `0`s written before the index, as in `$01`
+        leading_zeros: usize,
+        children: Vec<Node>,
+        span: Span,
+    },
+    /// `${1|one,two|}`
+    Choice {
+        index: u32,
+        /// `0`s written before the index, as in `$01`
+        leading_zeros: usize,
+        options: Vec<Text>,
+        span: Span,
+    },
+    /// `$NAME`, `${NAME}`, `${NAME:default}` or `${NAME/regex/format/flags}`
+    Variable {
+        name: String,
+        braced: bool,
+        default: Option<Vec<Node>>,
+        transform: Option<Transform>,
+        span: Span,
+    },
+}
+
+/// Literal text as written (`raw`) and with its escapes resolved (`value`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Text {
+    pub raw: String,
+    pub value: String,
+}
+
+/// `/regex/format/flags` applied to a tabstop or variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+    /// The regex as written, `\/` included
+    pub regex: String,
+    pub format: Vec<FormatItem>,
+    pub flags: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatItem {
+    Text(Text),
+    /// `$1` or `${1...}` referring to a capture group
+    Group {
+        index: u32,
+        /// `0`s written before the index, as in `$01`
+        leading_zeros: usize,
+        braced: bool,
+        kind: FormatKind,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatKind {
+    /// `$1` or `${1}`
+    Plain,
+    /// `${1:/upcase}`, `/downcase`, `/capitalize`, `/camelcase` or `/pascalcase`
+    Case(String),
+    /// `${1:+if}`
+    If(Text),
+    /// `${1:?if:else}`
+    IfElse(Text, Text),
+    /// `${1:-else}`, or `${1:else}` when `dash` is false
+    Else { text: Text, dash: bool },
+}
+
+impl Node {
+    pub fn span(&self) -> &Span {
+        match self {
+            Node::Text { span, .. }
+            | Node::Tabstop { span, .. }
+            | Node::Placeholder { span, .. }
+            | Node::Choice { span, .. }
+            | Node::Variable { span, .. } => span,
+        }
+    }
+
+    /// The tabstop index of tabstops, placeholders and choices.
+    pub fn index(&self) -> Option<u32> {
+        match self {
+            Node::Tabstop { index, .. }
+            | Node::Placeholder { index, .. }
+            | Node::Choice { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+
+    fn children(&self) -> &[Node] {
+        match self {
+            Node::Placeholder { children, .. } => children,
+            Node::Variable {
+                default: Some(children),
+                ..
+            } => children,
+            _ => &[],
+        }
+    }
+}
+
+impl Transform {
+    /// The regex with its `\/` escapes resolved.
+    pub fn pattern(&self) -> String {
+        self.regex.replace("\\/", "/")
+    }
+}
+
+impl SnippetAst {
+    /// All nodes depth-first, nested ones included.
+    pub fn walk(&self) -> Vec<&Node> {
+        fn visit<'a>(nodes: &'a [Node], out: &mut Vec<&'a Node>) {
+            for node in nodes {
+                out.push(node);
+                visit(node.children(), out);
+            }
+        }
+        let mut out = Vec::new();
+        visit(&self.nodes, &mut out);
+        out
+    }
+
+    /// Tabstop indices in order of navigation: `1`, `2`, ... and `$0` last.
+    pub fn tabstops(&self) -> Vec<u32> {
+        let mut indices: Vec<u32> = self.walk().iter().filter_map(|n| n.index()).collect();
+        indices.sort_by_key(|&index| (index == 0, index));
+        indices.dedup();
+        indices
+    }
+
+    pub fn placeholders(&self) -> Vec<&Node> {
+        self.walk()
+            .into_iter()
+            .filter(|n| matches!(n, Node::Placeholder { .. }))
+            .collect()
+    }
+
+    pub fn choices(&self) -> Vec<&Node> {
+        self.walk()
+            .into_iter()
+            .filter(|n| matches!(n, Node::Choice { .. }))
+            .collect()
+    }
+
+    /// Names of the variables used, in order of first use.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for node in self.walk() {
+            if let Node::Variable { name, .. } = node {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+}